@@ -2,10 +2,10 @@ use chrono::{Duration, Utc};
 use rhorizons::{ephemeris_orbital_elements, major_bodies};
 
 #[tokio::main]
-async fn main() {
+async fn main() -> Result<(), rhorizons::Error> {
     env_logger::init();
 
-    let bodies = major_bodies().await;
+    let bodies = major_bodies().await?;
 
     let earth = bodies
         .iter()
@@ -22,10 +22,12 @@ async fn main() {
         start_time, stop_time
     );
 
-    for elements in ephemeris_orbital_elements(earth.id, start_time, stop_time).await {
+    for elements in ephemeris_orbital_elements(earth.id, start_time, stop_time).await? {
         println!(
             "Eccentricity: {:?}, Semi-major axis: {:?}, Inclination: {:?}, Longitude of ascending node: {:?}, Argument of perifocus: {:?}, Mean anomaly: {:?}",
             elements.eccentricity, elements.semi_major_axis, elements.inclination, elements.longitude_of_ascending_node, elements.argument_of_perifocus, elements.mean_anomaly
         );
     }
+
+    Ok(())
 }
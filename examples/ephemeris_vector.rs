@@ -2,10 +2,10 @@ use chrono::{Duration, Utc};
 use rhorizons::{ephemeris_vector, major_bodies};
 
 #[tokio::main]
-async fn main() {
+async fn main() -> Result<(), rhorizons::Error> {
     env_logger::init();
 
-    let bodies = major_bodies().await;
+    let bodies = major_bodies().await?;
 
     let earth = bodies
         .iter()
@@ -22,10 +22,12 @@ async fn main() {
         start_time, stop_time
     );
 
-    for vectors in ephemeris_vector(earth.id, start_time, stop_time).await {
+    for vectors in ephemeris_vector(earth.id, start_time, stop_time).await? {
         println!(
             "position: {:?}, velocity: {:?}",
             vectors.position, vectors.velocity
         );
     }
+
+    Ok(())
 }
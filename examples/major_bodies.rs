@@ -1,12 +1,14 @@
 use rhorizons::major_bodies;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> Result<(), rhorizons::Error> {
     env_logger::init();
 
     println!("Major bodies in the Solar System.");
 
-    for body in major_bodies().await {
+    for body in major_bodies().await? {
         println!("{} ({})", body.name, body.id);
     }
+
+    Ok(())
 }
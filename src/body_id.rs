@@ -0,0 +1,255 @@
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::major_bodies::{BodyKind, MajorBody};
+
+/// Horizons identifier of a major body, e.g. `399` for the Earth.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#defs>
+///
+/// Example
+/// ```
+/// # use rhorizons::BodyId;
+/// assert_eq!(BodyId::EARTH, BodyId::from(399));
+/// assert_eq!("500@399", BodyId::EARTH.center());
+///
+/// // E.g. read from a configuration file.
+/// let moon: BodyId = "moon".parse().unwrap();
+/// assert_eq!(BodyId::MOON, moon);
+/// let titan: BodyId = "606".parse().unwrap();
+/// assert_eq!(BodyId::TITAN, titan);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BodyId(pub i32);
+
+/// Defines constants of well-known bodies, along with a table of their names
+/// used by [`BodyId::from_str`].
+macro_rules! well_known_bodies {
+    ($($(#[$doc:meta])* $name:ident = $id:expr,)*) => {
+        impl BodyId {
+            $(
+                $(#[$doc])*
+                pub const $name: BodyId = BodyId($id);
+            )*
+        }
+
+        const WELL_KNOWN_BODIES: &[(&str, BodyId)] = &[$((stringify!($name), BodyId::$name),)*];
+    };
+}
+
+well_known_bodies! {
+    /// Center of mass of the whole Solar System, `0`.
+    SOLAR_SYSTEM_BARYCENTER = 0,
+    /// Barycenter of the Mercury system, `1`.
+    MERCURY_BARYCENTER = 1,
+    /// Barycenter of the Venus system, `2`.
+    VENUS_BARYCENTER = 2,
+    /// Barycenter of the Earth-Moon system, `3`.
+    EARTH_MOON_BARYCENTER = 3,
+    /// Barycenter of the Mars system, `4`.
+    MARS_BARYCENTER = 4,
+    /// Barycenter of the Jupiter system, `5`.
+    JUPITER_BARYCENTER = 5,
+    /// Barycenter of the Saturn system, `6`.
+    SATURN_BARYCENTER = 6,
+    /// Barycenter of the Uranus system, `7`.
+    URANUS_BARYCENTER = 7,
+    /// Barycenter of the Neptune system, `8`.
+    NEPTUNE_BARYCENTER = 8,
+    /// Barycenter of the Pluto system, `9`.
+    PLUTO_BARYCENTER = 9,
+    /// The Sun, `10`.
+    SUN = 10,
+
+    /// Mercury, `199`.
+    MERCURY = 199,
+    /// Venus, `299`.
+    VENUS = 299,
+    /// The Earth, `399`.
+    EARTH = 399,
+    /// Mars, `499`.
+    MARS = 499,
+    /// Jupiter, `599`.
+    JUPITER = 599,
+    /// Saturn, `699`.
+    SATURN = 699,
+    /// Uranus, `799`.
+    URANUS = 799,
+    /// Neptune, `899`.
+    NEPTUNE = 899,
+    /// Pluto, `999`.
+    PLUTO = 999,
+
+    /// The Moon, `301`.
+    MOON = 301,
+    /// Phobos, `401`.
+    PHOBOS = 401,
+    /// Deimos, `402`.
+    DEIMOS = 402,
+    /// Io, `501`.
+    IO = 501,
+    /// Europa, `502`.
+    EUROPA = 502,
+    /// Ganymede, `503`.
+    GANYMEDE = 503,
+    /// Callisto, `504`.
+    CALLISTO = 504,
+    /// Mimas, `601`.
+    MIMAS = 601,
+    /// Enceladus, `602`.
+    ENCELADUS = 602,
+    /// Tethys, `603`.
+    TETHYS = 603,
+    /// Dione, `604`.
+    DIONE = 604,
+    /// Rhea, `605`.
+    RHEA = 605,
+    /// Titan, `606`.
+    TITAN = 606,
+    /// Hyperion, `607`.
+    HYPERION = 607,
+    /// Iapetus, `608`.
+    IAPETUS = 608,
+    /// Ariel, `701`.
+    ARIEL = 701,
+    /// Umbriel, `702`.
+    UMBRIEL = 702,
+    /// Titania, `703`.
+    TITANIA = 703,
+    /// Oberon, `704`.
+    OBERON = 704,
+    /// Miranda, `705`.
+    MIRANDA = 705,
+    /// Triton, `801`.
+    TRITON = 801,
+    /// Nereid, `802`.
+    NEREID = 802,
+    /// Charon, `901`.
+    CHARON = 901,
+}
+
+impl BodyId {
+    /// Value of the `COMMAND` parameter selecting this body as the target.
+    pub fn command(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Value of the `CENTER` parameter selecting the center of this body as
+    /// the origin, e.g. `500@399`.
+    pub fn center(&self) -> String {
+        format!("500@{}", self.0)
+    }
+
+    /// Kind of the body, told by its identifier.
+    pub fn kind(&self) -> BodyKind {
+        BodyKind::of(*self)
+    }
+
+    /// Barycenter of the planetary system the body belongs to, from `1` for
+    /// Mercury to `9` for Pluto. `None` for bodies outside of planetary
+    /// systems, e.g. the Sun or spacecraft.
+    pub fn parent_system(&self) -> Option<BodyId> {
+        match self.kind() {
+            BodyKind::Barycenter => Some(*self),
+            BodyKind::Planet | BodyKind::Satellite if self.0 < 1000 => Some(BodyId(self.0 / 100)),
+            BodyKind::Satellite => Some(BodyId(self.0 / 10_000)),
+            _ => None,
+        }
+    }
+
+    /// Planet of a satellite, or the planet itself, e.g. `599` for any of
+    /// Jupiter's moons.
+    pub fn parent_planet(&self) -> Option<BodyId> {
+        match self.kind() {
+            BodyKind::Planet | BodyKind::Satellite => self
+                .parent_system()
+                .map(|system| BodyId(system.0 * 100 + 99)),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for BodyId {
+    fn from(id: i32) -> Self {
+        BodyId(id)
+    }
+}
+
+impl From<BodyId> for i32 {
+    fn from(id: BodyId) -> Self {
+        id.0
+    }
+}
+
+impl From<&MajorBody> for BodyId {
+    fn from(body: &MajorBody) -> Self {
+        body.id
+    }
+}
+
+impl fmt::Display for BodyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Text that is neither a number nor the name of a well-known body, see
+/// [`BodyId::from_str`].
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("neither an identifier nor a well-known body: {0:?}")]
+pub struct BodyIdParseError(String);
+
+impl FromStr for BodyId {
+    type Err = BodyIdParseError;
+
+    /// Parse either a number, e.g. `399`, or the name of one of the constants,
+    /// ignoring case and treating spaces and dashes as underscores, e.g.
+    /// `earth` or `Earth-Moon barycenter`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(id) = s.parse() {
+            return Ok(BodyId(id));
+        }
+
+        let name = s.to_uppercase().replace([' ', '-'], "_");
+        WELL_KNOWN_BODIES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, id)| *id)
+            .ok_or_else(|| BodyIdParseError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_body_ids() {
+        assert_eq!(Ok(BodyId::EARTH), "399".parse());
+        assert_eq!(Ok(BodyId(-78000)), " -78000 ".parse());
+        assert_eq!(Ok(BodyId::EARTH), "earth".parse());
+        assert_eq!(
+            Ok(BodyId::EARTH_MOON_BARYCENTER),
+            "Earth-Moon barycenter".parse()
+        );
+        assert_eq!(
+            Ok(BodyId::SOLAR_SYSTEM_BARYCENTER),
+            "SOLAR_SYSTEM_BARYCENTER".parse()
+        );
+        assert_eq!(
+            Err(BodyIdParseError("Vulcan".to_string())),
+            "Vulcan".parse::<BodyId>()
+        );
+    }
+
+    #[test]
+    fn body_id_parameters() {
+        assert_eq!("399", BodyId::EARTH.command());
+        assert_eq!("500@399", BodyId::EARTH.center());
+        assert_eq!("500@0", BodyId::SOLAR_SYSTEM_BARYCENTER.center());
+        assert_eq!(Some(BodyId::JUPITER_BARYCENTER), BodyId::IO.parent_system());
+        assert_eq!(Some(BodyId::SATURN), BodyId::TITAN.parent_planet());
+    }
+}
@@ -1,130 +1,641 @@
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use thiserror::Error;
 
 use crate::{
+    body_id::BodyId,
     ephemeris::{
-        EphemerisOrbitalElementsItem, EphemerisOrbitalElementsParser, EphemerisVectorItem,
-        EphemerisVectorParser,
+        Ephemeris, EphemerisObserverItem, EphemerisOrbitalElementsItem, EphemerisVectorItem,
+        RiseTransitSet,
     },
-    major_bodies::MajorBody,
+    error::Error,
+    major_bodies::{MajorBody, MajorBodyColumns},
+    parse,
+    query::{EphemerisQuery, ObserverQuantity, ObserverSite, RiseSetHorizon},
 };
 
-/// Generic Horizons response. Their API just gives some JSON with two field,
-/// some statuses and `result` field which is just human-readable string
-/// normally seen in telnet or web API.
+/// Official Horizons API endpoint.
+pub const DEFAULT_BASE_URL: &str = "https://ssd.jpl.nasa.gov/api/horizons.api";
+
+/// Generic Horizons response. Their API just gives some JSON with a
+/// signature, an `error` field when the query is rejected and `result` field
+/// which is just human-readable string normally seen in telnet or web API.
 #[derive(Deserialize, Debug)]
 struct HorizonsResponse {
+    signature: Option<HorizonsSignature>,
+    error: Option<String>,
+    #[serde(default)]
     result: String,
 }
 
-#[derive(Error, Debug)]
-#[error("error returned from Horizons")]
-struct HorizonsQueryError;
+/// Identification of the API which produced the response.
+#[derive(Deserialize, Debug)]
+struct HorizonsSignature {
+    source: String,
+    version: String,
+}
+
+/// Messages which Horizons puts in `result` instead of the ephemeris.
+const NO_EPHEMERIS_MESSAGES: &[&str] = &["No ephemeris for target"];
+const UNKNOWN_BODY_MESSAGES: &[&str] = &[
+    "No matches found",
+    "No site matches",
+    "Cannot find central body",
+];
+const INVALID_QUERY_MESSAGES: &[&str] = &[
+    "Cannot use print-out interval",
+    "Cannot interpret",
+    "Bad dates",
+    "Projected output length",
+];
 
-/// Query the Horizons API, returning a result in form of lines.
-async fn query<T>(parameters: &T) -> Result<Vec<String>, HorizonsQueryError>
-where
-    T: Serialize,
-{
-    let result = reqwest::Client::new()
-        .get("https://ssd.jpl.nasa.gov/api/horizons.api")
-        .query(parameters)
-        .send()
-        .await
-        .map_err(|_| HorizonsQueryError)?
-        .json::<HorizonsResponse>()
-        .await
-        .map_err(|_| HorizonsQueryError)?
-        .result
-        .split('\n')
-        .map(str::to_owned)
-        .collect::<Vec<String>>();
+/// Recognize common failure messages in the `result` field. Only the part
+/// preceding the ephemeris is searched, the rest of a successful result may
+/// mention them, e.g. in the explanation of the columns.
+fn recognize_error(result: &[String]) -> Option<Error> {
+    let contains_any = |line: &str, messages: &[&str]| messages.iter().any(|m| line.contains(m));
+
+    let mut preamble = result.iter().take_while(|line| line.trim() != "$$SOE");
+    preamble.find_map(|line| {
+        let message = line.trim().to_string();
+        if contains_any(line, NO_EPHEMERIS_MESSAGES) {
+            Some(Error::NoEphemeris(message))
+        } else if contains_any(line, UNKNOWN_BODY_MESSAGES) {
+            Some(Error::UnknownBody(message))
+        } else if contains_any(line, INVALID_QUERY_MESSAGES) {
+            Some(Error::InvalidQuery(message))
+        } else {
+            None
+        }
+    })
+}
 
-    for line in &result {
-        log::trace!("{}", line);
+/// Make sure that the result contains an ephemeris. If it doesn't, whatever
+/// Horizons said instead is most likely an explanation.
+fn ensure_ephemeris<S: AsRef<str>>(result: &[S]) -> Result<(), Error> {
+    if result.iter().any(|line| line.as_ref().trim() == "$$SOE") {
+        Ok(())
+    } else {
+        Err(Error::Horizons(
+            result
+                .iter()
+                .map(|line| line.as_ref().trim())
+                .filter(|line| !line.is_empty() && !line.starts_with('*'))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ))
     }
+}
 
-    Ok(result)
+/// Delay between consecutive attempts of a failed query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// Always wait the same amount of time.
+    Constant(Duration),
+    /// Start with `initial` and double the delay after every failed attempt,
+    /// never waiting longer than `max`.
+    Exponential {
+        /// Delay after the first failed attempt.
+        initial: Duration,
+        /// Upper bound of the delay.
+        max: Duration,
+    },
 }
 
-async fn query_with_retries<T>(parameters: &T) -> Vec<String>
-where
-    T: Serialize,
-{
-    for n in 1..10 {
-        log::trace!("try {}", n);
-        if let Ok(result) = query(parameters).await {
-            return result;
+impl Backoff {
+    /// Delay to wait after `attempt` (counting from 1) failed.
+    fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            Backoff::Constant(delay) => delay,
+            Backoff::Exponential { initial, max } => initial
+                .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+                .map_or(max, |delay| delay.min(max)),
         }
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await
     }
-    // TODO: Don't panic.
-    panic!("max retries exceeded");
 }
 
-/// Get names and identifiers of all major bodies in the Solar System.
-pub async fn major_bodies() -> Vec<MajorBody> {
-    query_with_retries(&[("COMMAND", "MB")])
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::Constant(Duration::from_secs(1))
+    }
+}
+
+/// Builder for [`HorizonsClient`].
+///
+/// Example
+/// ```
+/// # use std::time::Duration;
+/// # use rhorizons::{Backoff, HorizonsClient};
+/// let client = HorizonsClient::builder()
+///     .base_url("http://localhost:8080/api/horizons.api")
+///     .timeout(Duration::from_secs(30))
+///     .max_retries(3)
+///     .backoff(Backoff::Constant(Duration::from_millis(100)))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HorizonsClientBuilder {
+    base_url: String,
+    timeout: Option<Duration>,
+    max_retries: u32,
+    backoff: Backoff,
+    csv_format: bool,
+}
+
+impl Default for HorizonsClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: None,
+            max_retries: 8,
+            backoff: Backoff::default(),
+            csv_format: false,
+        }
+    }
+}
+
+impl HorizonsClientBuilder {
+    /// URL of the Horizons API endpoint. Defaults to [`DEFAULT_BASE_URL`].
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Timeout of a single HTTP request. There is no timeout by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How many times a failed query is repeated before giving up. Defaults
+    /// to 8.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay between the attempts. Defaults to one second.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Whether ephemerides are requested as comma-separated tables, which are
+    /// parsed by the column names instead of fixed-width fields. Disabled by
+    /// default.
+    pub fn csv_format(mut self, csv_format: bool) -> Self {
+        self.csv_format = csv_format;
+        self
+    }
+
+    /// Create the client.
+    pub fn build(self) -> Result<HorizonsClient, Error> {
+        let mut http = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+
+        Ok(HorizonsClient {
+            http: http.build().map_err(Error::Transport)?,
+            base_url: self.base_url,
+            max_retries: self.max_retries,
+            backoff: self.backoff,
+            csv_format: self.csv_format,
+        })
+    }
+}
+
+/// Client of the Horizons API.
+///
+/// It keeps a connection pool, so it is a good idea to create it once and
+/// reuse it for subsequent queries.
+///
+/// Example
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), rhorizons::Error> {
+/// let client = rhorizons::HorizonsClient::default();
+///
+/// for body in client.major_bodies().await? {
+///     println!("{} ({})", body.name, body.id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HorizonsClient {
+    http: reqwest::Client,
+    base_url: String,
+    max_retries: u32,
+    backoff: Backoff,
+    csv_format: bool,
+}
+
+impl Default for HorizonsClient {
+    /// Client talking to the official Horizons API with default settings.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`reqwest::Client::new`], when TLS backend cannot be
+    /// initialized.
+    fn default() -> Self {
+        HorizonsClientBuilder::default()
+            .build()
+            .expect("could not create HTTP client")
+    }
+}
+
+impl HorizonsClient {
+    /// Start building a client with non-default settings.
+    pub fn builder() -> HorizonsClientBuilder {
+        HorizonsClientBuilder::default()
+    }
+
+    /// Query the Horizons API, returning a result in form of lines.
+    async fn query<T>(&self, parameters: &T) -> Result<Vec<String>, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let response = self
+            .http
+            .get(&self.base_url)
+            .query(parameters)
+            .send()
+            .await
+            .map_err(Error::Transport)?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(Error::Transport)?;
+
+        // A busy or failing server is worth another try, whatever the reason
+        // given in the body.
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            log::debug!("{}: {}", status, body);
+            return Err(Error::HttpStatus(status));
+        }
+
+        // Rejected queries come with an error status, but still have a JSON
+        // body explaining the reason.
+        let response = match serde_json::from_str::<HorizonsResponse>(&body) {
+            Ok(response) => response,
+            Err(_) if !status.is_success() => return Err(Error::HttpStatus(status)),
+            Err(err) => return Err(Error::Json(err)),
+        };
+
+        if let Some(signature) = &response.signature {
+            log::trace!("{} {}", signature.source, signature.version);
+        }
+
+        if let Some(error) = response.error {
+            return Err(Error::Horizons(error));
+        }
+
+        if !status.is_success() {
+            return Err(Error::HttpStatus(status));
+        }
+
+        let result = response
+            .result
+            .split('\n')
+            .map(str::to_owned)
+            .collect::<Vec<String>>();
+
+        for line in &result {
+            log::trace!("{}", line);
+        }
+
+        match recognize_error(&result) {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+
+    async fn query_with_retries<T>(&self, parameters: &T) -> Result<Vec<String>, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut attempt = 1;
+        loop {
+            log::trace!("try {}", attempt);
+            match self.query(parameters).await {
+                Ok(result) => return Ok(result),
+                Err(err) if !err.is_transient() => return Err(err),
+                Err(err) if attempt > self.max_retries => {
+                    return Err(Error::RetriesExhausted {
+                        attempts: attempt,
+                        last: Box::new(err),
+                    })
+                }
+                Err(err) => {
+                    log::debug!("attempt {} failed: {}", attempt, err);
+                    tokio::time::sleep(self.backoff.delay(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Parameters of requests making up the query, along with the settings of
+    /// the client.
+    fn ephemeris_requests(
+        &self,
+        query: &EphemerisQuery,
+        ephem_type: &str,
+    ) -> Vec<Vec<(&'static str, String)>> {
+        let mut requests = query.requests(ephem_type);
+        if self.csv_format {
+            for parameters in &mut requests {
+                parameters.push(("CSV_FORMAT", "YES".to_string()));
+            }
+        }
+        requests
+    }
+
+    /// Send every request making up the query, parsing each result with
+    /// `parse` and merging the entries under the header of the first one.
+    async fn query_ephemeris<Item>(
+        &self,
+        query: &EphemerisQuery,
+        ephem_type: &str,
+        parse: impl Fn(&[String]) -> Result<Ephemeris<Item>, Error>,
+    ) -> Result<Ephemeris<Item>, Error> {
+        let mut metadata = None;
+        let mut items = Vec::new();
+        for parameters in self.ephemeris_requests(query, ephem_type) {
+            let result = self.query_with_retries(&parameters).await?;
+
+            ensure_ephemeris(&result)?;
+            let ephemeris = parse(&result)?;
+            query.check_site(&ephemeris.metadata)?;
+            // Requests of the same query share the header.
+            metadata.get_or_insert(ephemeris.metadata);
+            items.extend(ephemeris.items);
+        }
+        Ok(Ephemeris {
+            metadata: metadata.unwrap_or_default(),
+            items,
+        })
+    }
+
+    /// Get names and identifiers of all major bodies in the Solar System.
+    pub async fn major_bodies(&self) -> Result<Vec<MajorBody>, Error> {
+        let result = self.query_with_retries(&[("COMMAND", "MB")]).await?;
+
+        let columns = result
+            .iter()
+            .find_map(|line| MajorBodyColumns::from_header(line))
+            .unwrap_or_default();
+        Ok(result
+            .iter()
+            .filter_map(|s| MajorBody::parse(s, &columns).ok())
+            .collect())
+    }
+
+    /// Get vector ephemeris (position and velocity) described by the query.
+    pub async fn vectors(
+        &self,
+        query: &EphemerisQuery,
+    ) -> Result<Ephemeris<EphemerisVectorItem>, Error> {
+        self.query_ephemeris(query, "VECTORS", |result| {
+            parse::vectors_from_lines(result.iter().map(String::as_str))
+        })
+        .await
+    }
+
+    /// Get orbital element ephemeris (e.g. eccentricity, semi-major axis, ...)
+    /// described by the query.
+    pub async fn orbital_elements(
+        &self,
+        query: &EphemerisQuery,
+    ) -> Result<Ephemeris<EphemerisOrbitalElementsItem>, Error> {
+        self.query_ephemeris(query, "ELEMENTS", |result| {
+            parse::orbital_elements_from_lines(result.iter().map(String::as_str))
+        })
+        .await
+    }
+
+    /// Get observer ephemeris (e.g. right ascension and declination)
+    /// described by the query. The center of the query is the observer.
+    pub async fn observer(
+        &self,
+        query: &EphemerisQuery,
+    ) -> Result<Ephemeris<EphemerisObserverItem>, Error> {
+        let mut ephemeris = self.observer_entries(query).await?;
+        ephemeris.items.retain(|item| query.is_visible(item));
+        Ok(ephemeris)
+    }
+
+    /// Observer ephemeris as listed by Horizons, without the visibility
+    /// checks of [`HorizonsClient::observer`].
+    async fn observer_entries(
+        &self,
+        query: &EphemerisQuery,
+    ) -> Result<Ephemeris<EphemerisObserverItem>, Error> {
+        self.query_ephemeris(query, "OBSERVER", |result| {
+            parse::observer_from_lines(result.iter().map(String::as_str))
+        })
+        .await
+    }
+
+    /// Get times when the target of the query rises, transits and sets, seen
+    /// from the site given as the center of the query. Events are kept even
+    /// when the target is not visible there, e.g. below the elevation given
+    /// by [`EphemerisQuery::visibility`].
+    pub async fn rise_transit_set(
+        &self,
+        query: &EphemerisQuery,
+        horizon: RiseSetHorizon,
+    ) -> Result<Vec<RiseTransitSet>, Error> {
+        let query = query.clone().rise_transit_set(horizon);
+        let ephemeris = self.observer_entries(&query).await?;
+        Ok(ephemeris
+            .iter()
+            .filter_map(RiseTransitSet::from_item)
+            .collect())
+    }
+
+    /// Get vector ephemeris (position and velocity) of a major body. Coordinates are
+    /// relative to the Sun's center.
+    pub async fn ephemeris_vector(
+        &self,
+        id: BodyId,
+        start_time: DateTime<Utc>,
+        stop_time: DateTime<Utc>,
+    ) -> Result<Ephemeris<EphemerisVectorItem>, Error> {
+        self.vectors(&EphemerisQuery::new(id, start_time, stop_time))
+            .await
+    }
+
+    /// Get orbital element ephemeris (e.g. eccentricity, semi-major axis, ...) of a
+    /// major body relative to the Sun's center
+    pub async fn ephemeris_orbital_elements(
+        &self,
+        id: BodyId,
+        start_time: DateTime<Utc>,
+        stop_time: DateTime<Utc>,
+    ) -> Result<Ephemeris<EphemerisOrbitalElementsItem>, Error> {
+        self.orbital_elements(&EphemerisQuery::new(id, start_time, stop_time))
+            .await
+    }
+
+    /// Get observer ephemeris of a major body, as seen from the observer
+    /// `site`, listing given quantities.
+    pub async fn ephemeris_observer(
+        &self,
+        id: BodyId,
+        site: impl Into<ObserverSite>,
+        start_time: DateTime<Utc>,
+        stop_time: DateTime<Utc>,
+        quantities: &[ObserverQuantity],
+    ) -> Result<Ephemeris<EphemerisObserverItem>, Error> {
+        self.observer(
+            &EphemerisQuery::new(id, start_time, stop_time)
+                .center(site)
+                .quantities(quantities),
+        )
         .await
-        .iter()
-        .filter_map(|s| MajorBody::try_from(s.as_str()).ok())
-        .collect()
+    }
+}
+
+/// Get names and identifiers of all major bodies in the Solar System.
+///
+/// Shortcut for [`HorizonsClient::major_bodies`] using a default client.
+pub async fn major_bodies() -> Result<Vec<MajorBody>, Error> {
+    HorizonsClient::default().major_bodies().await
 }
 
 /// Get vector ephemeris (position and velocity) of a major body. Coordinates are
 /// relative to the Sun's center.
+///
+/// Shortcut for [`HorizonsClient::ephemeris_vector`] using a default client.
 pub async fn ephemeris_vector(
-    id: i32,
+    id: BodyId,
     start_time: DateTime<Utc>,
     stop_time: DateTime<Utc>,
-) -> Vec<EphemerisVectorItem> {
-    let result = query_with_retries(&[
-        ("COMMAND", id.to_string().as_str()),
-        // Select Sun as a observer. Note that Solar System Barycenter is in a
-        // slightly different place.
-        // https://astronomy.stackexchange.com/questions/44851/
-        ("CENTER", "500@10"),
-        ("EPHEM_TYPE", "VECTORS"),
-        // https://ssd.jpl.nasa.gov/horizons/manual.html#time
-        (
-            "START_TIME",
-            start_time.format("%Y-%b-%d-%T").to_string().as_str(),
-        ),
-        (
-            "STOP_TIME",
-            stop_time.format("%Y-%b-%d-%T").to_string().as_str(),
-        ),
-    ])
-    .await;
-
-    EphemerisVectorParser::parse(result.iter().map(String::as_str)).collect()
+) -> Result<Ephemeris<EphemerisVectorItem>, Error> {
+    HorizonsClient::default()
+        .ephemeris_vector(id, start_time, stop_time)
+        .await
 }
+
 /// Get orbital element ephemeris (e.g. eccentricity, semi-major axis, ...) of a
 /// major body relative to the Sun's center
+///
+/// Shortcut for [`HorizonsClient::ephemeris_orbital_elements`] using a default
+/// client.
 pub async fn ephemeris_orbital_elements(
-    id: i32,
+    id: BodyId,
     start_time: DateTime<Utc>,
     stop_time: DateTime<Utc>,
-) -> Vec<EphemerisOrbitalElementsItem> {
-    let result = query_with_retries(&[
-        ("COMMAND", id.to_string().as_str()),
-        // Select Sun as a observer. Note that Solar System Barycenter is in a
-        // slightly different place.
-        // https://astronomy.stackexchange.com/questions/44851/
-        ("CENTER", "500@10"),
-        ("EPHEM_TYPE", "ELEMENTS"),
-        // https://ssd.jpl.nasa.gov/horizons/manual.html#time
-        (
-            "START_TIME",
-            start_time.format("%Y-%b-%d-%T").to_string().as_str(),
-        ),
-        (
-            "STOP_TIME",
-            stop_time.format("%Y-%b-%d-%T").to_string().as_str(),
-        ),
-    ])
-    .await;
-
-    EphemerisOrbitalElementsParser::parse(result.iter().map(String::as_str)).collect()
+) -> Result<Ephemeris<EphemerisOrbitalElementsItem>, Error> {
+    HorizonsClient::default()
+        .ephemeris_orbital_elements(id, start_time, stop_time)
+        .await
+}
+
+/// Get observer ephemeris of a major body, as seen from the observer `site`,
+/// listing given quantities.
+///
+/// Shortcut for [`HorizonsClient::ephemeris_observer`] using a default client.
+pub async fn ephemeris_observer(
+    id: BodyId,
+    site: impl Into<ObserverSite>,
+    start_time: DateTime<Utc>,
+    stop_time: DateTime<Utc>,
+    quantities: &[ObserverQuantity],
+) -> Result<Ephemeris<EphemerisObserverItem>, Error> {
+    HorizonsClient::default()
+        .ephemeris_observer(id, site, start_time, stop_time, quantities)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_delays() {
+        let constant = Backoff::Constant(Duration::from_secs(1));
+        assert_eq!(Duration::from_secs(1), constant.delay(1));
+        assert_eq!(Duration::from_secs(1), constant.delay(5));
+
+        let exponential = Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+        };
+        assert_eq!(Duration::from_millis(100), exponential.delay(1));
+        assert_eq!(Duration::from_millis(200), exponential.delay(2));
+        assert_eq!(Duration::from_millis(800), exponential.delay(4));
+        assert_eq!(Duration::from_secs(1), exponential.delay(5));
+        assert_eq!(Duration::from_secs(1), exponential.delay(100));
+    }
+
+    fn lines(result: &str) -> Vec<String> {
+        result.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn recognizing_horizons_errors() {
+        let result = lines(
+            "*******************************************************************************
+ No ephemeris for target \"Mars\" prior to A.D. 1600-JAN-01 00:00:00.0000 TDB
+*******************************************************************************",
+        );
+        assert!(matches!(
+            recognize_error(&result),
+            Some(Error::NoEphemeris(message)) if message.starts_with("No ephemeris for target")
+        ));
+
+        let result = lines(" Cannot use print-out interval <= zero.");
+        assert!(matches!(
+            recognize_error(&result),
+            Some(Error::InvalidQuery(_))
+        ));
+
+        let result =
+            lines(" Projected output length (~105121) exceeds 90024 line max -- change step-size");
+        assert!(matches!(
+            recognize_error(&result),
+            Some(Error::InvalidQuery(_))
+        ));
+
+        // Mentions of the limit are not errors by themselves.
+        let result = lines(" Output is limited to 90024 lines, see the line max setting.");
+        assert!(recognize_error(&result).is_none());
+
+        let result = lines(" No matches found.");
+        assert!(matches!(
+            recognize_error(&result),
+            Some(Error::UnknownBody(_))
+        ));
+
+        let result = lines(include_str!("vector.txt"));
+        assert!(recognize_error(&result).is_none());
+        assert!(ensure_ephemeris(&result).is_ok());
+
+        // Messages following the start of the ephemeris are part of it.
+        let result = lines(
+            "$$SOE
+$$EOE
+ Cannot interpret the date of an entry exceeding the line max of 80 characters.",
+        );
+        assert!(recognize_error(&result).is_none());
+    }
+
+    #[test]
+    fn results_without_ephemeris() {
+        let result = lines(
+            "*******************************************************************************
+ Something went wrong.
+
+ Really wrong.
+*******************************************************************************",
+        );
+        assert!(matches!(
+            ensure_ephemeris(&result),
+            Err(Error::Horizons(message)) if message == "Something went wrong.\nReally wrong."
+        ));
+    }
 }
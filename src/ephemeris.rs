@@ -1,8 +1,59 @@
+use std::{collections::BTreeMap, ops::Deref};
+
 use chrono::{DateTime, NaiveDateTime, Utc};
 
-use crate::utilities::{take_expecting, take_or_empty};
+use crate::{
+    error::ParseError,
+    metadata::EphemerisMetadata,
+    query::VectorTable,
+    table::{FromTableEntry, Layout, ObserverRow, TableEntry, TableParser},
+    time::{JulianDate, TimeScale},
+    utilities::take_expecting,
+};
+
+/// Entries of an ephemeris along with its description, read from the header
+/// preceding them. Dereferences to the slice of entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ephemeris<T> {
+    /// Description of the ephemeris, e.g. the frame of its coordinates.
+    pub metadata: EphemerisMetadata,
+    /// Entries in chronological order.
+    pub items: Vec<T>,
+}
+
+impl<T> Deref for Ephemeris<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T> IntoIterator for Ephemeris<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Ephemeris<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
 
-/// Position (in km) and velocity (in km/s) of a body.
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Position (in km) and velocity (in km/s) of a body, along with light-time
+/// and range. Which of them are present depends on the [`VectorTable`] of the
+/// query, quantities not listed by Horizons are `None`.
+///
+/// Units below are those of the default
+/// [`OutputUnits::KmS`](crate::OutputUnits::KmS). Other units are recorded in
+/// [`EphemerisMetadata::output_units`].
 ///
 /// | Horizons Symbol | Meaning                                         | Unit                  |
 /// |-----------------|-------------------------------------------------|-----------------------|
@@ -15,23 +66,38 @@ use crate::utilities::{take_expecting, take_or_empty};
 /// | LT              | One-way down-leg Newtonian light-time           | sec                   |
 /// | RG              | Range; distance from coordinate center          | km                    |
 /// | RR              | Range-rate; radial velocity wrt coord. center   | km/sec                |
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EphemerisVectorItem {
     /// Timestamp of the entry in UTC
     pub time: DateTime<Utc>,
+    /// Julian date of the entry, in the time scale of the query
+    pub julian_date: JulianDate,
 
-    /// Position int km of the moving body relative to the Sun
+    /// Position int km of the moving body relative to the center
     ///
     /// [x, y, z]
-    pub position: [f32; 3],
+    pub position: Option<[f64; 3]>,
 
-    /// Velocity in km/s of the moving body relative to the Sun
+    /// Velocity in km/s of the moving body relative to the center
     ///
     /// [v_x, v_y, v_z]
-    pub velocity: [f32; 3],
+    pub velocity: Option<[f64; 3]>,
+
+    /// One-way down-leg Newtonian light-time in s
+    pub light_time: Option<f64>,
+
+    /// Distance from the center in km
+    pub range: Option<f64>,
+
+    /// Radial velocity relative to the center in km/s
+    pub range_rate: Option<f64>,
 }
 
-/// Orbital Elements of a body. Units are km, s and degrees
+/// Orbital Elements of a body.
+///
+/// Units below are those of the default
+/// [`OutputUnits::KmS`](crate::OutputUnits::KmS). Other units are recorded in
+/// [`EphemerisMetadata::output_units`].
 ///
 /// | Horizons Symbol | Meaning                     | Mathematical Symbol | Unit                |
 /// |-----------------|-----------------------------|---------------------|---------------------|
@@ -49,469 +115,869 @@ pub struct EphemerisVectorItem {
 /// | PR              | Sidereal orbit period       |                     | sec                 |
 ///
 /// For a detailed explenation of keplarian orbital elements, visit [Wikipedia](https://en.wikipedia.org/wiki/Orbital_elements)
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EphemerisOrbitalElementsItem {
     /// Timestamp of the entry in UTC
     pub time: DateTime<Utc>,
+    /// Julian date of the entry, in the time scale of the query
+    pub julian_date: JulianDate,
 
     /// Describes the "roundness" of the orbit.
     ///
     /// Value of 0 means a circle, everything until 1 is an eliptic orbit.  
     /// A value of 1 is a parabolic trajectory and everythin greater 1 a hyperbolic trajectory.  
     /// See <https://en.wikipedia.org/wiki/Eccentricity_(orbit)>
-    pub eccentricity: f32,
+    pub eccentricity: f64,
     /// Distance from the center to the nearest point of the orbit in kilometer (km)
     ///
     /// See <https://en.wikipedia.org/wiki/Apsis>
-    pub periapsis_distance: f32,
+    pub periapsis_distance: f64,
     /// Tilt of the orbit
     ///
     /// Expressed in degrees in reference to the X-Y plane  
     /// For futher information see <https://en.wikipedia.org/wiki/Inclination>
-    pub inclination: f32,
+    pub inclination: f64,
 
     /// The point, were the orbit crosses the reference plane (X-Y plane) from south to north
     ///
     /// The unit of this value is in degrees.  
     /// <https://en.wikipedia.org/wiki/Longitude_of_the_ascending_node>
-    pub longitude_of_ascending_node: f32,
+    pub longitude_of_ascending_node: f64,
     /// Angle in degrees of the periapsis to the ascending node, in the direction of motion.
     ///
     /// <https://en.wikipedia.org/wiki/Argument_of_periapsis>
-    pub argument_of_perifocus: f32,
+    pub argument_of_perifocus: f64,
     /// The timestamp (Julian Day Number) at which the body reaches the periapsis of the orbit
     ///
     /// <https://en.wikipedia.org/wiki/Apsis#Time_of_perihelion>
-    pub time_of_periapsis: f32,
+    pub time_of_periapsis: f64,
 
     /// The angular speed (degrees/sec) of a body to complete one orbit
     ///
     /// Assumes constant speed in a circular orbit.  
     /// <https://en.wikipedia.org/wiki/Mean_motion>
-    pub mean_motion: f32,
+    pub mean_motion: f64,
     /// Orbital distance from the periapsis to the moving body.
     ///
     /// The angle in degrees is in reference to a circular orbit.  
     /// <https://en.wikipedia.org/wiki/Mean_anomaly>
-    pub mean_anomaly: f32,
+    pub mean_anomaly: f64,
     /// Angle in degrees between the moving body and the periapsis of the orbit.
     ///
     /// The angle is defined in relation to the main focus point.  
     /// <https://en.wikipedia.org/wiki/True_anomaly>
-    pub true_anomaly: f32,
+    pub true_anomaly: f64,
 
     /// The sum of the periapsis and apoapsis distances divided by two in kilometer (km)
     ///
     /// <https://en.wikipedia.org/wiki/Semimajor_axis>
-    pub semi_major_axis: f32,
+    pub semi_major_axis: f64,
     /// Distance from the center to the farthest point of the orbit in kilometer (km)
     ///
     /// <https://en.wikipedia.org/wiki/Apsis>
-    pub apoapsis_distance: f32,
+    pub apoapsis_distance: f64,
     /// Time to complete on orbit in seconds
     ///
     /// Sidereal refers to the default period of an orbit.  
     /// <https://en.wikipedia.org/wiki/Orbital_period>
-    pub siderral_orbit_period: f32,
-}
-
-enum EphemerisVectorParserState {
-    WaitingForSoe,
-    WaitingForDate,
-    Date(DateTime<Utc>),
-    Position {
-        time: DateTime<Utc>,
-        position: [f32; 3],
-    },
-    Complete {
-        time: DateTime<Utc>,
-        position: [f32; 3],
-        velocity: [f32; 3],
-    },
-    End,
-}
-
-enum EphemerisOrbitalElementsParserState {
-    WaitingForSoe,
-    WaitingForDate,
-    Date(DateTime<Utc>),
-    FirstRow {
-        time: DateTime<Utc>,
-
-        eccentricity: f32,
-        periapsis_distance: f32,
-        inclination: f32,
-    },
-    SecondRow {
-        time: DateTime<Utc>,
-
-        eccentricity: f32,
-        periapsis_distance: f32,
-        inclination: f32,
-
-        longitude_of_ascending_node: f32,
-        argument_of_perifocus: f32,
-        time_of_periapsis: f32,
-    },
-    ThirdRow {
-        time: DateTime<Utc>,
-
-        eccentricity: f32,
-        periapsis_distance: f32,
-        inclination: f32,
-
-        longitude_of_ascending_node: f32,
-        argument_of_perifocus: f32,
-        time_of_periapsis: f32,
-
-        mean_motion: f32,
-        mean_anomaly: f32,
-        true_anomaly: f32,
-    },
-    End,
-}
-
-pub struct EphemerisVectorParser<'a, Input: Iterator<Item = &'a str>> {
-    state: EphemerisVectorParserState,
-    input: Input,
-}
-
-pub struct EphemerisOrbitalElementsParser<'a, Input: Iterator<Item = &'a str>> {
-    state: EphemerisOrbitalElementsParserState,
-    input: Input,
-}
-
-impl<'a, Input: Iterator<Item = &'a str>> EphemerisVectorParser<'a, Input> {
-    pub fn parse(input: Input) -> Self {
-        Self {
-            state: EphemerisVectorParserState::WaitingForSoe,
-            input,
+    pub siderral_orbit_period: f64,
+}
+
+/// Presence of the Sun at the site of an observer, flagged by Horizons next
+/// to the date. Ordered from the darkest, so that e.g. `presence <=
+/// SolarPresence::NauticalTwilight` means the Sun is at least nautical
+/// twilight deep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum SolarPresence {
+    /// Night, or an observer at the center of a body, ` `.
+    #[default]
+    Night,
+    /// Astronomical twilight or dawn, `A`.
+    AstronomicalTwilight,
+    /// Nautical twilight or dawn, `N`.
+    NauticalTwilight,
+    /// Civil twilight or dawn, `C`.
+    CivilTwilight,
+    /// Upper limb of the Sun on or above the horizon, `*`.
+    Daylight,
+}
+
+impl SolarPresence {
+    /// Presence given by its flag.
+    pub(crate) fn from_flag(flag: char) -> Option<Self> {
+        match flag {
+            ' ' => Some(SolarPresence::Night),
+            'A' => Some(SolarPresence::AstronomicalTwilight),
+            'N' => Some(SolarPresence::NauticalTwilight),
+            'C' => Some(SolarPresence::CivilTwilight),
+            '*' => Some(SolarPresence::Daylight),
+            _ => None,
         }
     }
 }
 
-impl<'a, Input: Iterator<Item = &'a str>> EphemerisOrbitalElementsParser<'a, Input> {
-    pub fn parse(input: Input) -> Self {
-        Self {
-            state: EphemerisOrbitalElementsParserState::WaitingForSoe,
-            input,
+/// Presence of the Moon at the site of an observer, flagged by Horizons next
+/// to the date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LunarPresence {
+    /// Upper limb of the Moon below the horizon, or an observer at the center
+    /// of a body, ` `.
+    #[default]
+    BelowHorizon,
+    /// Upper limb of the Moon on or above the horizon, `m`.
+    AboveHorizon,
+}
+
+impl LunarPresence {
+    /// Presence given by its flag.
+    pub(crate) fn from_flag(flag: char) -> Option<Self> {
+        match flag {
+            ' ' => Some(LunarPresence::BelowHorizon),
+            'm' => Some(LunarPresence::AboveHorizon),
+            _ => None,
         }
     }
 }
 
-impl<'a, Input: Iterator<Item = &'a str>> Iterator for EphemerisVectorParser<'a, Input> {
-    type Item = EphemerisVectorItem;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(line) = self.input.next() {
-                match self.state {
-                    EphemerisVectorParserState::WaitingForSoe => {
-                        if line == "$$SOE" {
-                            self.state = EphemerisVectorParserState::WaitingForDate;
-                        }
-                    }
-                    EphemerisVectorParserState::WaitingForDate => {
-                        if line == "$$EOE" {
-                            self.state = EphemerisVectorParserState::End;
-                        } else {
-                            let time = parse_date_time(line);
-
-                            self.state = EphemerisVectorParserState::Date(time);
-                        }
-                    }
-                    EphemerisVectorParserState::Date(time) => {
-                        // TODO: Don't panic.
-                        let line = take_expecting(line, " X =").unwrap();
-                        let (x, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " Y =").unwrap();
-                        let (y, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " Z =").unwrap();
-                        let (z, _) = take_or_empty(line, 22);
-
-                        self.state = EphemerisVectorParserState::Position {
-                            time,
-                            position: [
-                                x.trim().parse::<f32>().unwrap(),
-                                y.trim().parse::<f32>().unwrap(),
-                                z.trim().parse::<f32>().unwrap(),
-                            ],
-                        };
-                    }
-                    EphemerisVectorParserState::Position { time, position } => {
-                        // TODO: Don't panic.
-                        let line = take_expecting(line, " VX=").unwrap();
-                        let (vx, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " VY=").unwrap();
-                        let (vy, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " VZ=").unwrap();
-                        let (vz, _) = take_or_empty(line, 22);
-
-                        self.state = EphemerisVectorParserState::Complete {
-                            time,
-                            position,
-                            velocity: [
-                                vx.trim().parse::<f32>().unwrap(),
-                                vy.trim().parse::<f32>().unwrap(),
-                                vz.trim().parse::<f32>().unwrap(),
-                            ],
-                        };
-                    }
-                    // Would parse third line and then return Item => ignores third line and returns directly
-                    EphemerisVectorParserState::Complete {
-                        time,
-                        position,
-                        velocity,
-                    } => {
-                        self.state = EphemerisVectorParserState::WaitingForDate;
-                        return Some(EphemerisVectorItem {
-                            time,
-                            position,
-                            velocity,
-                        });
-                    }
-                    EphemerisVectorParserState::End => {
-                        // Should we drain input iterator?
-                        return None;
-                    }
-                }
-            } else {
-                // Input iterator is drained. Nothing to do.
-                return None;
-            }
+/// Event of the target seen from a site on the surface of a body, flagged by
+/// Horizons next to the date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TargetEvent {
+    /// Target rises above the horizon, `r`.
+    Rise,
+    /// Target crosses the meridian, `t`.
+    Transit,
+    /// Target sets below the horizon, `s`.
+    Set,
+}
+
+impl TargetEvent {
+    /// Event given by its flag, `None` for any other.
+    pub(crate) fn from_flag(flag: char) -> Option<Self> {
+        match flag {
+            'r' => Some(TargetEvent::Rise),
+            't' => Some(TargetEvent::Transit),
+            's' => Some(TargetEvent::Set),
+            _ => None,
+        }
+    }
+}
+
+/// Side of the Sun the target is on, seen by the observer, marked by Horizons
+/// after the solar elongation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SolarElongationSide {
+    /// Target leads the Sun, i.e. is in the morning sky, `/L`.
+    Leading,
+    /// Target trails the Sun, i.e. is in the evening sky, `/T`.
+    Trailing,
+}
+
+impl SolarElongationSide {
+    /// Side given by its marker, `None` for any other, e.g. `/?` when
+    /// Horizons cannot tell.
+    pub(crate) fn from_marker(marker: &str) -> Option<Self> {
+        match marker {
+            "/L" => Some(SolarElongationSide::Leading),
+            "/T" => Some(SolarElongationSide::Trailing),
+            _ => None,
         }
     }
 }
 
-impl<'a, Input: Iterator<Item = &'a str>> Iterator for EphemerisOrbitalElementsParser<'a, Input> {
-    type Item = EphemerisOrbitalElementsItem;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(line) = self.input.next() {
-                match self.state {
-                    EphemerisOrbitalElementsParserState::WaitingForSoe => {
-                        if line == "$$SOE" {
-                            self.state = EphemerisOrbitalElementsParserState::WaitingForDate;
-                        }
-                    }
-                    EphemerisOrbitalElementsParserState::WaitingForDate => {
-                        if line == "$$EOE" {
-                            self.state = EphemerisOrbitalElementsParserState::End;
-                        } else {
-                            let time = parse_date_time(line);
-
-                            self.state = EphemerisOrbitalElementsParserState::Date(time);
-                        }
-                    }
-                    EphemerisOrbitalElementsParserState::Date(time) => {
-                        let line = take_expecting(line, " EC=").unwrap();
-                        let (eccentricity, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " QR=").unwrap();
-                        let (periapsis_distance, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " IN=").unwrap();
-                        let (inclination, _) = take_or_empty(line, 22);
-
-                        self.state = EphemerisOrbitalElementsParserState::FirstRow {
-                            time,
-
-                            eccentricity: eccentricity.trim().parse::<f32>().unwrap(),
-                            periapsis_distance: periapsis_distance.trim().parse::<f32>().unwrap(),
-                            inclination: inclination.trim().parse::<f32>().unwrap(),
-                        };
-                    }
-                    EphemerisOrbitalElementsParserState::FirstRow {
-                        time,
-
-                        eccentricity,
-                        periapsis_distance,
-                        inclination,
-                    } => {
-                        let line = take_expecting(line, " OM=").unwrap();
-                        let (longitude_of_ascending_node, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " W =").unwrap();
-                        let (argument_of_perifocus, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " Tp=").unwrap();
-                        let (time_of_periapsis, _) = take_or_empty(line, 22);
-
-                        self.state = EphemerisOrbitalElementsParserState::SecondRow {
-                            time,
-
-                            eccentricity,
-                            periapsis_distance,
-                            inclination,
-
-                            longitude_of_ascending_node: longitude_of_ascending_node
-                                .trim()
-                                .parse::<f32>()
-                                .unwrap(),
-                            argument_of_perifocus: argument_of_perifocus
-                                .trim()
-                                .parse::<f32>()
-                                .unwrap(),
-                            time_of_periapsis: time_of_periapsis.trim().parse::<f32>().unwrap(),
-                        };
-                    }
-                    EphemerisOrbitalElementsParserState::SecondRow {
-                        time,
-
-                        eccentricity,
-                        periapsis_distance,
-                        inclination,
-
-                        longitude_of_ascending_node,
-                        argument_of_perifocus,
-                        time_of_periapsis,
-                    } => {
-                        let line = take_expecting(line, " N =").unwrap();
-                        let (mean_motion, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " MA=").unwrap();
-                        let (mean_anomaly, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " TA=").unwrap();
-                        let (true_anomaly, _) = take_or_empty(line, 22);
-
-                        self.state = EphemerisOrbitalElementsParserState::ThirdRow {
-                            time,
-
-                            eccentricity,
-                            periapsis_distance,
-                            inclination,
-
-                            longitude_of_ascending_node,
-                            argument_of_perifocus,
-                            time_of_periapsis,
-
-                            mean_motion: mean_motion.trim().parse::<f32>().unwrap(),
-                            mean_anomaly: mean_anomaly.trim().parse::<f32>().unwrap(),
-                            true_anomaly: true_anomaly.trim().parse::<f32>().unwrap(),
-                        };
-                    }
-                    // Parses last line and return Item
-                    EphemerisOrbitalElementsParserState::ThirdRow {
-                        time,
-
-                        eccentricity,
-                        periapsis_distance,
-                        inclination,
-
-                        longitude_of_ascending_node,
-                        argument_of_perifocus,
-                        time_of_periapsis,
-
-                        mean_motion,
-                        mean_anomaly,
-                        true_anomaly,
-                    } => {
-                        let line = take_expecting(line, " A =").unwrap();
-                        let (semi_major_axis, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " AD=").unwrap();
-                        let (apoapsis_distance, line) = take_or_empty(line, 22);
-
-                        let line = take_expecting(line, " PR=").unwrap();
-                        let (siderral_orbit_period, _) = take_or_empty(line, 22);
-
-                        self.state = EphemerisOrbitalElementsParserState::WaitingForDate;
-                        return Some(EphemerisOrbitalElementsItem {
-                            time,
-
-                            eccentricity,
-                            periapsis_distance,
-                            inclination,
-
-                            longitude_of_ascending_node,
-                            argument_of_perifocus,
-                            time_of_periapsis,
-
-                            mean_motion,
-                            mean_anomaly,
-                            true_anomaly,
-
-                            semi_major_axis: semi_major_axis.trim().parse::<f32>().unwrap(),
-                            apoapsis_distance: apoapsis_distance.trim().parse::<f32>().unwrap(),
-                            siderral_orbit_period: siderral_orbit_period
-                                .trim()
-                                .parse::<f32>()
-                                .unwrap(),
-                        });
-                    }
-                    EphemerisOrbitalElementsParserState::End => {
-                        // Should we drain input iterator?
-                        return None;
-                    }
+/// Position of a body on the sky of an observer. Which quantities are
+/// present depends on the [`ObserverQuantity`](crate::ObserverQuantity)s of
+/// the query, quantities not listed by Horizons are `None`, as are those
+/// Horizons marks as not available, e.g. airmass of a target below the
+/// horizon.
+///
+/// Angles are in degrees, whether Horizons prints them sexagesimal or
+/// decimal.
+///
+/// | Horizons Column         | Meaning                                  | Unit        |
+/// |-------------------------|------------------------------------------|-------------|
+/// | R.A._____(ICRF)_____DEC | Astrometric right ascension, declination | degrees     |
+/// | R.A.__(a-apparent)__DEC | Apparent right ascension, declination    | degrees     |
+/// | Azi_(a-app)_Elev        | Apparent azimuth and elevation           | degrees     |
+/// | a-mass                  | Relative optical airmass                 |             |
+/// | mag_ex                  | Visual magnitude extinction              | mag         |
+/// | APmag                   | Apparent visual magnitude                | mag         |
+/// | S-brt                   | Surface brightness                       | mag/arcsec² |
+/// | T-mag                   | Total magnitude of a comet               | mag         |
+/// | N-mag                   | Nuclear magnitude of a comet             | mag         |
+/// | Illu%                   | Illuminated fraction of the disk         | percent     |
+/// | S-O-T /r                | Solar elongation, leading or trailing    | degrees     |
+/// | S-T-O, phi              | Sun-Target-Observer phase angle          | degrees     |
+#[derive(Debug, Clone, PartialEq)]
+pub struct EphemerisObserverItem {
+    /// Timestamp of the entry in UTC
+    pub time: DateTime<Utc>,
+    /// Julian date of the entry, in the time scale of the table, UT or TT
+    pub julian_date: JulianDate,
+
+    /// Presence of the Sun at the site
+    pub solar_presence: SolarPresence,
+    /// Presence of the Moon at the site
+    pub lunar_presence: LunarPresence,
+    /// Rise, transit or set of the target at the time of the entry
+    pub event: Option<TargetEvent>,
+
+    /// Astrometric right ascension in the reference system of the query
+    pub astrometric_ra: Option<f64>,
+    /// Astrometric declination in the reference system of the query
+    pub astrometric_dec: Option<f64>,
+
+    /// Apparent right ascension, relative to the true equator and equinox of
+    /// date
+    pub apparent_ra: Option<f64>,
+    /// Apparent declination, relative to the true equator of date
+    pub apparent_dec: Option<f64>,
+
+    /// Apparent azimuth, measured from the north towards the east
+    pub azimuth: Option<f64>,
+    /// Apparent elevation above the horizon
+    pub elevation: Option<f64>,
+
+    /// Relative optical airmass, 1 at the zenith
+    pub airmass: Option<f64>,
+    /// Visual magnitude extinction due to the airmass
+    pub magnitude_extinction: Option<f64>,
+
+    /// Apparent visual magnitude, of any target but comets
+    pub apparent_magnitude: Option<f64>,
+    /// Surface brightness, in visual magnitudes per square arcsecond
+    pub surface_brightness: Option<f64>,
+    /// Total magnitude of a comet, nucleus and coma
+    pub total_magnitude: Option<f64>,
+    /// Magnitude of the nucleus of a comet
+    pub nuclear_magnitude: Option<f64>,
+
+    /// Illuminated fraction of the disk seen by the observer, in percent
+    pub illumination: Option<f64>,
+    /// Angle between the Sun and the target seen by the observer
+    pub solar_elongation: Option<f64>,
+    /// Whether the target leads or trails the Sun
+    pub solar_elongation_side: Option<SolarElongationSide>,
+    /// Angle between the Sun and the observer seen from the target
+    pub phase_angle: Option<f64>,
+}
+
+impl EphemerisObserverItem {
+    /// Whether the target is at least `min_elevation` degrees above the
+    /// horizon while the Sun is no brighter than `max_solar_presence`.
+    /// Elevation is not checked when it is not listed.
+    pub fn is_visible(&self, min_elevation: f64, max_solar_presence: SolarPresence) -> bool {
+        self.elevation
+            .is_none_or(|elevation| elevation >= min_elevation)
+            && self.solar_presence <= max_solar_presence
+    }
+}
+
+/// Rise, transit or set of a body seen from a site on the surface of another
+/// one, see [`HorizonsClient::rise_transit_set`](crate::HorizonsClient::rise_transit_set).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiseTransitSet {
+    /// What happens to the body
+    pub event: TargetEvent,
+    /// Time of the event in UTC
+    pub time: DateTime<Utc>,
+    /// Julian date of the event, in the time scale of the table, UT or TT
+    pub julian_date: JulianDate,
+    /// Apparent azimuth at the event in degrees, measured from the north
+    /// towards the east
+    pub azimuth: f64,
+    /// Apparent elevation at the event in degrees
+    pub elevation: f64,
+}
+
+impl RiseTransitSet {
+    /// Event of an observer entry, if it is flagged as one and lists azimuth
+    /// and elevation.
+    pub(crate) fn from_item(item: &EphemerisObserverItem) -> Option<Self> {
+        Some(RiseTransitSet {
+            event: item.event?,
+            time: item.time,
+            julian_date: item.julian_date,
+            azimuth: item.azimuth?,
+            elevation: item.elevation?,
+        })
+    }
+}
+
+/// Values of any table, by the labels listed in its header, e.g. `X` or
+/// `EC`. Useful for layouts without a dedicated item type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EphemerisTableItem {
+    /// Timestamp of the entry in UTC
+    pub time: DateTime<Utc>,
+    /// Julian date of the entry, in the time scale of the query
+    pub julian_date: JulianDate,
+    /// Values of the quantities by their labels
+    pub values: BTreeMap<String, f64>,
+}
+
+/// Both representations of the timestamp of an entry.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EntryTime {
+    pub(crate) julian_date: JulianDate,
+    pub(crate) time: DateTime<Utc>,
+}
+
+/// Labels of the quantities of a vector table, see [`Layout`].
+fn vector_layout(table: VectorTable) -> Layout {
+    let position = ["X", "Y", "Z"];
+    let velocity = ["VX", "VY", "VZ"];
+    let range = ["LT", "RG", "RR"];
+
+    let rows: &[[&str; 3]] = match table {
+        VectorTable::Position => &[position],
+        VectorTable::State => &[position, velocity],
+        VectorTable::StateAndRange => &[position, velocity, range],
+        VectorTable::PositionAndRange => &[position, range],
+        VectorTable::Velocity => &[velocity],
+        VectorTable::Range => &[range],
+    };
+    rows.iter()
+        .map(|row| row.iter().map(|label| label.to_string()).collect())
+        .collect()
+}
+
+impl FromTableEntry for EphemerisVectorItem {
+    fn default_layout() -> Option<Layout> {
+        Some(vector_layout(VectorTable::default()))
+    }
+
+    fn layout_for_format(format: &str) -> Result<Option<Layout>, ParseError> {
+        parse_vector_table(format).map(|table| Some(vector_layout(table)))
+    }
+
+    fn from_entry(entry: &TableEntry) -> Result<Self, ParseError> {
+        Ok(EphemerisVectorItem {
+            time: entry.time.time,
+            julian_date: entry.time.julian_date,
+            position: entry.vector3(["X", "Y", "Z"])?,
+            velocity: entry.vector3(["VX", "VY", "VZ"])?,
+            light_time: entry.get("LT"),
+            range: entry.get("RG"),
+            range_rate: entry.get("RR"),
+        })
+    }
+}
+
+impl FromTableEntry for EphemerisOrbitalElementsItem {
+    fn default_layout() -> Option<Layout> {
+        Some(
+            [
+                ["EC", "QR", "IN"],
+                ["OM", "W", "Tp"],
+                ["N", "MA", "TA"],
+                ["A", "AD", "PR"],
+            ]
+            .iter()
+            .map(|row| row.iter().map(|label| label.to_string()).collect())
+            .collect(),
+        )
+    }
+
+    fn from_entry(entry: &TableEntry) -> Result<Self, ParseError> {
+        Ok(EphemerisOrbitalElementsItem {
+            time: entry.time.time,
+            julian_date: entry.time.julian_date,
+
+            eccentricity: entry.require("EC")?,
+            periapsis_distance: entry.require("QR")?,
+            inclination: entry.require("IN")?,
+
+            longitude_of_ascending_node: entry.require("OM")?,
+            argument_of_perifocus: entry.require("W")?,
+            time_of_periapsis: entry.require("Tp")?,
+
+            mean_motion: entry.require("N")?,
+            mean_anomaly: entry.require("MA")?,
+            true_anomaly: entry.require("TA")?,
+
+            semi_major_axis: entry.require("A")?,
+            apoapsis_distance: entry.require("AD")?,
+            siderral_orbit_period: entry.require("PR")?,
+        })
+    }
+}
+
+impl FromTableEntry for EphemerisTableItem {
+    fn default_layout() -> Option<Layout> {
+        None
+    }
+
+    fn from_entry(entry: &TableEntry) -> Result<Self, ParseError> {
+        Ok(EphemerisTableItem {
+            time: entry.time.time,
+            julian_date: entry.time.julian_date,
+            values: entry
+                .labels
+                .iter()
+                .cloned()
+                .zip(entry.values.iter().copied())
+                .collect(),
+        })
+    }
+}
+
+impl FromTableEntry for EphemerisObserverItem {
+    const HEADER: &'static str = "Date__";
+
+    fn default_layout() -> Option<Layout> {
+        None
+    }
+
+    fn from_entry(_entry: &TableEntry) -> Result<Self, ParseError> {
+        Err(ParseError::Expected(Self::HEADER))
+    }
+
+    fn from_observer_row(row: &ObserverRow) -> Result<Self, ParseError> {
+        let mut flags = row.flags.chars().chain(std::iter::repeat(' '));
+        let mut item = EphemerisObserverItem {
+            time: row.time.time,
+            julian_date: row.time.julian_date,
+            solar_presence: flags
+                .next()
+                .and_then(SolarPresence::from_flag)
+                .ok_or(ParseError::Expected("solar presence"))?,
+            lunar_presence: flags
+                .next()
+                .and_then(LunarPresence::from_flag)
+                .ok_or(ParseError::Expected("lunar presence"))?,
+            event: flags.next().and_then(TargetEvent::from_flag),
+            astrometric_ra: None,
+            astrometric_dec: None,
+            apparent_ra: None,
+            apparent_dec: None,
+            azimuth: None,
+            elevation: None,
+            airmass: None,
+            magnitude_extinction: None,
+            apparent_magnitude: None,
+            surface_brightness: None,
+            total_magnitude: None,
+            nuclear_magnitude: None,
+            illumination: None,
+            solar_elongation: None,
+            solar_elongation_side: None,
+            phase_angle: None,
+        };
+
+        for &(label, text) in &row.fields {
+            if text.starts_with("n.a.") {
+                continue;
+            }
+            let tokens: Vec<_> = text.split_whitespace().collect();
+
+            if let Some((apparent, has_ra, has_dec)) = parse_ra_dec_label(label) {
+                let (ra, dec) = if apparent {
+                    (&mut item.apparent_ra, &mut item.apparent_dec)
+                } else {
+                    (&mut item.astrometric_ra, &mut item.astrometric_dec)
+                };
+
+                if has_ra && has_dec {
+                    let (ra_tokens, dec_tokens) = tokens.split_at(tokens.len() / 2);
+                    *ra = Some(parse_angle(ra_tokens, true)?);
+                    *dec = Some(parse_angle(dec_tokens, false)?);
+                } else if has_ra {
+                    *ra = Some(parse_angle(&tokens, true)?);
+                } else {
+                    *dec = Some(parse_angle(&tokens, false)?);
                 }
-            } else {
-                // Input iterator is drained. Nothing to do.
-                return None;
+            } else if label.starts_with("Azi") && label.ends_with("Elev") {
+                let [azimuth, elevation] = parse_numbers(&tokens)?;
+                item.azimuth = Some(azimuth);
+                item.elevation = Some(elevation);
+            } else if label.starts_with("Azi") {
+                item.azimuth = Some(parse_number(&tokens)?);
+            } else if label.starts_with("Elev") {
+                item.elevation = Some(parse_number(&tokens)?);
+            } else if label == "a-mass" {
+                item.airmass = Some(parse_number(&tokens)?);
+            } else if label == "mag_ex" {
+                item.magnitude_extinction = Some(parse_number(&tokens)?);
+            } else if label == "APmag" {
+                item.apparent_magnitude = Some(parse_number(&tokens)?);
+            } else if label == "S-brt" {
+                item.surface_brightness = Some(parse_number(&tokens)?);
+            } else if label == "T-mag" {
+                item.total_magnitude = Some(parse_number(&tokens)?);
+            } else if label == "N-mag" {
+                item.nuclear_magnitude = Some(parse_number(&tokens)?);
+            } else if label == "Illu%" {
+                item.illumination = Some(parse_number(&tokens)?);
+            } else if label == "S-O-T" {
+                item.solar_elongation = Some(parse_number(&tokens)?);
+            } else if label == "/r" {
+                item.solar_elongation_side = SolarElongationSide::from_marker(text);
+            } else if label == "S-T-O" || label == "phi" {
+                item.phase_angle = Some(parse_number(&tokens)?);
             }
         }
+
+        Ok(item)
     }
 }
 
-fn parse_date_time(line: &str) -> DateTime<Utc> {
-    let date_time_str: &str = line.split_terminator('=').collect::<Vec<_>>()[1].trim();
+pub(crate) type EphemerisVectorParser<'a, Input> = TableParser<'a, Input, EphemerisVectorItem>;
+pub(crate) type EphemerisOrbitalElementsParser<'a, Input> =
+    TableParser<'a, Input, EphemerisOrbitalElementsItem>;
+pub(crate) type EphemerisObserverParser<'a, Input> = TableParser<'a, Input, EphemerisObserverItem>;
+pub(crate) type EphemerisTableParser<'a, Input> = TableParser<'a, Input, EphemerisTableItem>;
+
+/// Read the label of a right ascension or declination column, e.g.
+/// `R.A.__(a-apparent)__DEC`, or `R.A._(ICRF)` and `DEC__(ICRF)` in CSV
+/// tables, as whether the coordinates are apparent, and whether the column
+/// holds the right ascension and the declination. Other columns, like the
+/// uncertainties `RA_3sigma` and `DEC_3sigma`, give `None`.
+fn parse_ra_dec_label(label: &str) -> Option<(bool, bool, bool)> {
+    let parts: Vec<_> = label.split('_').filter(|part| !part.is_empty()).collect();
+    let (frame, has_ra, has_dec) = match parts.as_slice() {
+        ["R.A.", frame, "DEC"] => (*frame, true, true),
+        ["R.A.", frame] => (*frame, true, false),
+        ["DEC", frame] => (*frame, false, true),
+        _ => return None,
+    };
+    let apparent = match frame {
+        "(ICRF)" | "(FK4)" => false,
+        "(a-apparent)" | "(a-app)" => true,
+        _ => return None,
+    };
+    Some((apparent, has_ra, has_dec))
+}
 
-    let date_time_str = take_expecting(date_time_str, "A.D. ").unwrap();
+/// Parse the value of the `Output format` header line, e.g.
+/// `   : 3 (position, velocity, LT, range, range-rate)`.
+fn parse_vector_table(format: &str) -> Result<VectorTable, ParseError> {
+    let code = format.trim_start_matches([' ', ':']);
+    code.get(..1)
+        .and_then(|code| code.parse().ok())
+        .and_then(VectorTable::from_code)
+        .ok_or(ParseError::Expected("vector table"))
+}
+
+/// Parse the first line of an entry, e.g.
+/// `2459750.250000000 = A.D. 2022-Jun-19 18:00:00.0000 TDB`.
+pub(crate) fn parse_date_time(line: &str) -> Result<EntryTime, ParseError> {
+    let (julian_date, date_time_str) = line.split_once('=').ok_or(ParseError::Expected("="))?;
 
-    //let line = line.trim_end_matches("TDB").trim();
-    //let line = line.trim_end_matches(".0000");
-    let (time, _) = take_or_empty(date_time_str, 20); //Somehow the formatter does not like %.4f
+    let julian_date = julian_date
+        .parse::<JulianDate>()
+        .map_err(ParseError::InvalidNumber)?;
 
-    NaiveDateTime::parse_from_str(time, "%Y-%b-%d %H:%M:%S")
-        .unwrap()
-        .and_utc()
+    Ok(EntryTime {
+        julian_date,
+        time: parse_calendar_time(date_time_str, TimeScale::Tdb)?,
+    })
+}
+
+/// Parse calendar date and time, e.g. `A.D. 2022-Jun-19 18:00:00.0000 TDB`,
+/// converting it to UTC. `time_scale` is used when the suffix is missing.
+pub(crate) fn parse_calendar_time(
+    date_time_str: &str,
+    time_scale: TimeScale,
+) -> Result<DateTime<Utc>, ParseError> {
+    let date_time_str =
+        take_expecting(date_time_str.trim(), "A.D. ").map_err(|_| ParseError::Expected("A.D. "))?;
+
+    let mut parts = date_time_str.split_whitespace();
+    let date = parts.next().unwrap_or_default();
+    let time = parts.next().unwrap_or_default();
+
+    // Time scale follows the time, except for CSV tables, which name it in
+    // the header.
+    let time_scale = match parts.next() {
+        Some(suffix) => TimeScale::from_suffix(suffix).ok_or(ParseError::Expected("time scale"))?,
+        None => time_scale,
+    };
+
+    let time = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%b-%d %H:%M:%S%.f")
+        .map_err(ParseError::InvalidDateTime)?;
+
+    Ok(time_scale.to_utc(time))
+}
+
+/// Parse a single number.
+fn parse_number(tokens: &[&str]) -> Result<f64, ParseError> {
+    let [number] = parse_numbers(tokens)?;
+    Ok(number)
+}
+
+/// Parse given count of numbers.
+fn parse_numbers<const N: usize>(tokens: &[&str]) -> Result<[f64; N], ParseError> {
+    if tokens.len() != N {
+        return Err(ParseError::Expected("number"));
+    }
+    let mut numbers = [0.0; N];
+    for (number, token) in numbers.iter_mut().zip(tokens) {
+        *number = token.parse().map_err(ParseError::InvalidNumber)?;
+    }
+    Ok(numbers)
+}
+
+/// Parse an angle given in degrees, either decimal or sexagesimal, e.g.
+/// `+18 09 50.6`. Sexagesimal right ascension, e.g. `03 26 01.13`, is given
+/// in hours.
+fn parse_angle(tokens: &[&str], hours: bool) -> Result<f64, ParseError> {
+    match tokens {
+        [degrees] => degrees.parse().map_err(ParseError::InvalidNumber),
+        [whole, minutes, seconds] => {
+            let negative = whole.starts_with('-');
+            let whole: f64 = whole
+                .trim_start_matches(['+', '-'])
+                .parse()
+                .map_err(ParseError::InvalidNumber)?;
+            let minutes: f64 = minutes.parse().map_err(ParseError::InvalidNumber)?;
+            let seconds: f64 = seconds.parse().map_err(ParseError::InvalidNumber)?;
+
+            let angle = whole + minutes / 60.0 + seconds / 3600.0;
+            let angle = if negative { -angle } else { angle };
+            Ok(if hours { angle * 15.0 } else { angle })
+        }
+        _ => Err(ParseError::Expected("angle")),
+    }
 }
 
 #[cfg(test)]
+// Values are copied verbatim from Horizons output.
+#[allow(clippy::excessive_precision)]
 mod tests {
-    use chrono::TimeZone;
+    use chrono::{Duration, NaiveDate, TimeZone};
 
     use super::*;
+    use crate::{
+        body_id::BodyId,
+        error::Error,
+        metadata::{EphemerisBody, ReferenceFrame},
+        query::{OutputUnits, ReferencePlane, ReferenceSystem, VectorCorrection},
+    };
+
+    fn tdb(year: i32, month: u32, day: u32, hour: u32, min: u32, sec: u32) -> DateTime<Utc> {
+        TimeScale::Tdb.to_utc(
+            NaiveDate::from_ymd_opt(year, month, day)
+                .unwrap()
+                .and_hms_opt(hour, min, sec)
+                .unwrap(),
+        )
+    }
 
     #[test]
     fn test_parsing_ephemeris_vector() {
         let data = include_str!("vector.txt");
-        let ephem: Vec<_> = EphemerisVectorParser::parse(data.lines()).collect();
+        let mut parser = EphemerisVectorParser::parse(data.lines());
+        let ephem = (&mut parser).collect::<Result<Vec<_>, _>>().unwrap();
         assert_eq!(4, ephem.len());
-        // TODO: This will probably fail intermittently due to float comparison.
+        assert_eq!(
+            Some(ReferenceFrame {
+                plane: ReferencePlane::Ecliptic,
+                system: ReferenceSystem::Icrf
+            }),
+            parser.into_metadata().reference_frame
+        );
         assert_eq!(
             EphemerisVectorItem {
-                time: Utc.with_ymd_and_hms(2022, 8, 13, 19, 55, 56).unwrap(), // A.D. 2022-Aug-13 19:55:56.0000 TDB
-                position: [
+                time: tdb(2022, 8, 13, 19, 55, 56), // A.D. 2022-Aug-13 19:55:56.0000 TDB
+                julian_date: JulianDate::new(2459805, 0.330509259),
+                position: Some([
                     1.870010427985840E+02,
                     2.484687803242536E+03,
                     -5.861602653492581E+03
-                ],
+                ]),
 
-                velocity: [
+                velocity: Some([
                     -3.362664133558439E-01,
                     1.344100266143978E-02,
                     -5.030275220358716E-03
-                ]
+                ]),
+
+                light_time: Some(2.124544811851363E-02),
+                range: Some(6.369225112760676E+03),
+                range_rate: Some(1.673381117437352E-18),
+            },
+            ephem[0]
+        );
+    }
+
+    #[test]
+    fn test_parsing_vector_tables() {
+        let data = "Output format   : 1 (position only)
+$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 Y = 2.484687803242536E+03 Z =-5.861602653492581E+03
+2459805.372175926 = A.D. 2022-Aug-13 20:55:56.0000 TDB
+ X =-1.016435490451044E+03 Y = 2.387580047487610E+03 Z =-5.816644131078659E+03
+$$EOE";
+        let ephem = EphemerisVectorParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, ephem.len());
+        assert_eq!(
+            Some([
+                -1.016435490451044E+03,
+                2.387580047487610E+03,
+                -5.816644131078659E+03
+            ]),
+            ephem[1].position
+        );
+        assert_eq!(None, ephem[1].velocity);
+        assert_eq!(None, ephem[1].range);
+
+        let data = "Output format   : 4 (position, LT, range, range-rate)
+$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 Y = 2.484687803242536E+03 Z =-5.861602653492581E+03
+ LT= 2.124544811851363E-02 RG= 6.369225112760676E+03 RR= 1.673381117437352E-18
+$$EOE";
+        let ephem = EphemerisVectorParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(1, ephem.len());
+        assert!(ephem[0].position.is_some());
+        assert_eq!(None, ephem[0].velocity);
+        assert_eq!(Some(6.369225112760676E+03), ephem[0].range);
+
+        let data = "Output format   : 6 (LT, range, and range-rate)
+$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ LT= 2.124544811851363E-02 RG= 6.369225112760676E+03 RR= 1.673381117437352E-18
+$$EOE";
+        let ephem = EphemerisVectorParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            EphemerisVectorItem {
+                time: tdb(2022, 8, 13, 19, 55, 56),
+                julian_date: JulianDate::new(2459805, 0.330509259),
+                position: None,
+                velocity: None,
+                light_time: Some(2.124544811851363E-02),
+                range: Some(6.369225112760676E+03),
+                range_rate: Some(1.673381117437352E-18),
             },
             ephem[0]
         );
+
+        let data = "Output format   : 9 (unknown)
+$$SOE
+$$EOE";
+        let ephem: Vec<_> = EphemerisVectorParser::parse(data.lines()).collect();
+        assert!(matches!(
+            ephem[..],
+            [Err(Error::Parse {
+                line: 1,
+                reason: ParseError::Expected("vector table"),
+                ..
+            })]
+        ));
+    }
+
+    #[test]
+    fn test_parsing_csv_tables() {
+        let data = "Output format   : 3 (position, velocity, LT, range, range-rate)
+*******************************************************************************
+            JDTDB,            Calendar Date (TDB),                      X,                      Y,                      Z,                     VX,                     VY,                     VZ,                     LT,                     RG,                     RR,
+*******************************************************************************
+$$SOE
+2459805.330509259, A.D. 2022-Aug-13 19:55:56.0000,  1.870010427985840E+02,  2.484687803242536E+03, -5.861602653492581E+03, -3.362664133558439E-01,  1.344100266143978E-02, -5.030275220358716E-03,  2.124544811851363E-02,  6.369225112760676E+03,  1.673381117437352E-18,
+2459805.372175926, A.D. 2022-Aug-13 20:55:56.0000, -1.016435490451044E+03,  2.387580047487610E+03, -5.816644131078659E+03, -3.284654645755883E-01, -6.707957715403978E-02,  2.986362783432212E-02,  2.124544811851362E-02,  6.369225112760672E+03,  2.231174823249804E-17,
+$$EOE";
+        let csv = EphemerisVectorParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let fixed_width = EphemerisVectorParser::parse(include_str!("vector.txt").lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(fixed_width[..2], csv[..]);
+
+        let data = "            JDUT,            Calendar Date (UT),                     EC,                     QR,                     IN,                     OM,                      W,                     Tp,                      N,                     MA,                     TA,                      A,                     AD,                     PR,
+$$SOE
+2459750.250000000, A.D. 2022-Jun-19 18:00:00.0000,  1.711794334680415E-02,  1.469885520304013E+08,  3.134746902320420E-03,  1.633896137466430E+02,  3.006492364709574E+02,  2459584.392523936927,  1.141316101270797E-05,  1.635515780663357E+02,  1.640958153023696E+02,  1.495485150384278E+08,  1.521084780464543E+08,  3.154253230977451E+07,
+$$EOE";
+        let ephem = EphemerisOrbitalElementsParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(1, ephem.len());
+        assert_eq!(
+            Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap(),
+            ephem[0].time
+        );
+        assert_eq!(1.711794334680415E-02, ephem[0].eccentricity);
+        assert_eq!(2459584.392523936927, ephem[0].time_of_periapsis);
+        assert_eq!(3.154253230977451E+07, ephem[0].siderral_orbit_period);
+
+        // Missing column.
+        let data = "JDTDB, Calendar Date (TDB), EC, QR,
+$$SOE
+2459750.250000000, A.D. 2022-Jun-19 18:00:00.0000, 1.711794334680415E-02, 1.469885520304013E+08,
+$$EOE";
+        let ephem: Vec<_> = EphemerisOrbitalElementsParser::parse(data.lines()).collect();
+        assert!(matches!(
+            ephem[..],
+            [Err(Error::Parse {
+                line: 3,
+                reason: ParseError::Expected("IN"),
+                ..
+            })]
+        ));
+
+        // Truncated row.
+        let data = "JDTDB, Calendar Date (TDB), X, Y, Z,
+$$SOE
+2459805.330509259, A.D. 2022-Aug-13 19:55:56.0000, 1.870010427985840E+02
+$$EOE";
+        let ephem: Vec<_> = EphemerisVectorParser::parse(data.lines()).collect();
+        assert!(matches!(
+            ephem[..],
+            [Err(Error::Parse {
+                line: 3,
+                reason: ParseError::Expected(","),
+                ..
+            })]
+        ));
     }
 
     #[test]
     fn test_parsing_ephemeris_orbital_elements() {
         let data = include_str!("orbital_elements.txt");
-        let ephem: Vec<_> = EphemerisOrbitalElementsParser::parse(data.lines()).collect();
+        let mut parser = EphemerisOrbitalElementsParser::parse(data.lines());
+        let ephem = (&mut parser).collect::<Result<Vec<_>, _>>().unwrap();
         assert_eq!(4, ephem.len());
-        // TODO: This will probably fail intermittently due to float comparison.
+        assert_eq!(
+            EphemerisMetadata {
+                target: Some(EphemerisBody {
+                    name: "Earth".to_string(),
+                    id: Some(BodyId::EARTH),
+                    source: Some("DE441".to_string()),
+                }),
+                center: Some(EphemerisBody {
+                    name: "Sun".to_string(),
+                    id: Some(BodyId::SUN),
+                    source: Some("DE441".to_string()),
+                }),
+                center_site: Some("BODY CENTER".to_string()),
+                center_geodetic: Some([0.0, 0.0, 0.0]),
+                center_cylindric: Some([0.0, 0.0, 0.0]),
+                center_radii: Some([696000.0, 696000.0, 696000.0]),
+                keplerian_gm: Some(1.3271283864171489E+11),
+                start_time: Some(tdb(2022, 6, 19, 18, 0, 0)),
+                stop_time: Some(tdb(2022, 6, 19, 21, 0, 0)),
+                step_size: Some("60 minutes".to_string()),
+                reference_frame: Some(ReferenceFrame {
+                    plane: ReferencePlane::Ecliptic,
+                    system: ReferenceSystem::Icrf
+                }),
+                output_units: Some(OutputUnits::KmS),
+                vector_correction: Some(VectorCorrection::Geometric),
+            },
+            parser.into_metadata()
+        );
         assert_eq!(
             EphemerisOrbitalElementsItem {
-                time: Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap(), // A.D. 2022-Jun-19 18:00:00.0000 TDB
+                time: tdb(2022, 6, 19, 18, 0, 0), // A.D. 2022-Jun-19 18:00:00.0000 TDB
+                julian_date: JulianDate::new(2459750, 0.25),
 
                 eccentricity: 1.711794334680415E-02,
                 periapsis_distance: 1.469885520304013E+08,
@@ -543,16 +1009,349 @@ mod tests {
         ];
 
         let expected: [DateTime<Utc>; 4] = [
-            Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap(),
-            Utc.with_ymd_and_hms(2022, 6, 19, 21, 0, 0).unwrap(),
-            Utc.with_ymd_and_hms(2022, 8, 13, 20, 55, 56).unwrap(),
-            Utc.with_ymd_and_hms(2022, 8, 13, 22, 55, 56).unwrap(),
+            tdb(2022, 6, 19, 18, 0, 0),
+            tdb(2022, 6, 19, 21, 0, 0),
+            tdb(2022, 8, 13, 20, 55, 56),
+            tdb(2022, 8, 13, 22, 55, 56),
         ];
 
         for (i, line) in lines.into_iter().enumerate() {
-            let time = parse_date_time(line);
+            let time = parse_date_time(line).unwrap();
 
-            assert_eq!(time, expected[i]);
+            assert_eq!(time.time, expected[i]);
+            assert_eq!(time.julian_date.to_string(), line[..17]);
         }
+
+        // TDB is ahead of UTC by 69.184 s, give or take 2 ms.
+        let time = parse_date_time(lines[0]).unwrap().time;
+        let utc =
+            Utc.with_ymd_and_hms(2022, 6, 19, 17, 58, 50).unwrap() + Duration::milliseconds(816);
+        assert!((time - utc).num_milliseconds().abs() <= 2);
+
+        assert_eq!(
+            Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap(),
+            parse_date_time("2459750.249189 = A.D. 2022-Jun-19 18:00:00.0000 UT")
+                .unwrap()
+                .time
+        );
+        assert_eq!(
+            Utc.with_ymd_and_hms(2022, 6, 19, 17, 58, 50).unwrap(),
+            parse_date_time("2459750.250000000 = A.D. 2022-Jun-19 18:00:00.0000 TT")
+                .unwrap()
+                .time
+                - Duration::milliseconds(816)
+        );
+
+        // Fractional seconds are kept.
+        let time =
+            parse_date_time("2459750.250001447 = A.D. 2022-Jun-19 18:00:00.1250 UT").unwrap();
+        assert_eq!(
+            Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap() + Duration::milliseconds(125),
+            time.time
+        );
+        assert_eq!(JulianDate::new(2459750, 0.250001447), time.julian_date);
+    }
+
+    #[test]
+    fn error_cases_when_parsing_ephemeris() {
+        let data = "$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 Y = 2.484687803242536E+03 Z =-5.861602653492581E+03
+ VX=-3.362664133558439E-01 VY= garbage              VZ=-5.030275220358716E-03
+$$EOE";
+        let ephem: Vec<_> = EphemerisVectorParser::parse(data.lines()).collect();
+        assert_eq!(1, ephem.len());
+        assert!(matches!(
+            &ephem[0],
+            Err(Error::Parse {
+                line: 4,
+                text,
+                reason: ParseError::InvalidNumber(_)
+            }) if text.starts_with(" VX=")
+        ));
+
+        let data = "$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 Y = 2.484687803242536E+03 Z =-5.861602653492581E+03
+ VX=-3.362664133558439E-01 VY= 1.344100266143978E-02 VZ=-5.030275220358716E-03
+$$EOE";
+        let ephem: Vec<_> = EphemerisVectorParser::parse(data.lines()).collect();
+        assert!(matches!(
+            ephem[..],
+            [Err(Error::Parse {
+                line: 5,
+                reason: ParseError::Expected(" LT="),
+                ..
+            })]
+        ));
+
+        let data = "$$SOE
+2459750.250000000 = A.D. 2022-Jun-19 18:00:00.0000 TDB
+ EC= 1.711794334680415E-02 QR= 1.469885520304013E+08 IN= 3.134746902320420E-03
+ XX= 1.633896137466430E+02 W = 3.006492364709574E+02 Tp=  2459584.392523936927";
+        let ephem: Vec<_> = EphemerisOrbitalElementsParser::parse(data.lines()).collect();
+        assert_eq!(1, ephem.len());
+        assert!(matches!(
+            ephem[0],
+            Err(Error::Parse {
+                line: 4,
+                reason: ParseError::Expected(" OM="),
+                ..
+            })
+        ));
+
+        let data = "$$SOE
+2459750.250000000 = A.D. 2022-Jun-19 18:00:00.0000 TDB
+ EC= 1.711794334680415E-02 QR= 1.469885520304013E+08 IN= 3.134746902320420E-03";
+        let ephem: Vec<_> = EphemerisOrbitalElementsParser::parse(data.lines()).collect();
+        assert!(matches!(
+            ephem[..],
+            [Err(Error::Parse {
+                line: 3,
+                reason: ParseError::UnexpectedEnd,
+                ..
+            })]
+        ));
+
+        assert!(matches!(
+            parse_date_time("2459750.250000000 = 2022-Jun-19 18:00:00.0000 TDB"),
+            Err(ParseError::Expected("A.D. "))
+        ));
+        assert!(matches!(
+            parse_date_time("2459750.25x = A.D. 2022-Jun-19 18:00:00.0000 TDB"),
+            Err(ParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_date_time("2459750.250000000 = A.D. 2022-Jux-19 18:00:00.0000 TDB"),
+            Err(ParseError::InvalidDateTime(_))
+        ));
+        assert!(matches!(
+            parse_date_time("2459750.250000000 = A.D. 2022-Jun-19 18:00:00.0000 XYZ"),
+            Err(ParseError::Expected("time scale"))
+        ));
+    }
+
+    fn assert_angle(expected: f64, actual: Option<f64>) {
+        let actual = actual.unwrap();
+        assert!(
+            (expected - actual).abs() < 1e-9,
+            "{} != {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn parsing_observer_table() {
+        let mut parser = EphemerisObserverParser::parse(include_str!("observer.txt").lines());
+        let items = (&mut parser).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(4, items.len());
+        assert_eq!(
+            Utc.with_ymd_and_hms(2022, 8, 13, 12, 0, 0).unwrap(),
+            items[2].time
+        );
+        // 03 26 01.13 +18 09 50.6
+        assert_angle(
+            (3.0 + 26.0 / 60.0 + 1.13 / 3600.0) * 15.0,
+            items[0].astrometric_ra,
+        );
+        assert_angle(18.0 + 9.0 / 60.0 + 50.6 / 3600.0, items[0].astrometric_dec);
+        // 03 29 44.63 +18 21 54.9
+        assert_angle(
+            (3.0 + 29.0 / 60.0 + 44.63 / 3600.0) * 15.0,
+            items[3].apparent_ra,
+        );
+        assert_angle(18.0 + 21.0 / 60.0 + 54.9 / 3600.0, items[3].apparent_dec);
+
+        let metadata = parser.into_metadata();
+        assert_eq!(
+            Some(EphemerisBody {
+                name: "Mars".to_string(),
+                id: Some(BodyId::MARS),
+                source: Some("mar097".to_string()),
+            }),
+            metadata.target
+        );
+        assert_eq!(Some("GEOCENTRIC".to_string()), metadata.center_site);
+    }
+
+    #[test]
+    fn parsing_decimal_and_csv_tables() {
+        let data = " Date__(TT)__HR:MN:SC.fff, , ,R.A._(ICRF),DEC__(ICRF),R.A._(a-app),DEC_(a-app),
+$$SOE
+ 2022-Aug-13 00:00:00.000,*,m,  51.50471,  -3.16406,n.a.,n.a.,
+$$EOE";
+        let items = EphemerisObserverParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            TimeScale::Tt.to_utc(
+                NaiveDateTime::parse_from_str("2022-08-13 00:00", "%Y-%m-%d %H:%M").unwrap()
+            ),
+            items[0].time
+        );
+        assert_eq!(JulianDate::new(2459804, 0.5), items[0].julian_date);
+        assert_eq!(Some(51.50471), items[0].astrometric_ra);
+        assert_eq!(Some(-3.16406), items[0].astrometric_dec);
+        assert_eq!(None, items[0].apparent_ra);
+        assert_eq!(SolarPresence::Daylight, items[0].solar_presence);
+        assert_eq!(LunarPresence::AboveHorizon, items[0].lunar_presence);
+
+        let data = " Date__(UT)__HR:MN     R.A.___(ICRF)___DEC
+$$SOE
+ 2022-Aug-13 00:00     51.50471 -3.16406
+$$EOE";
+        let items = EphemerisObserverParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(Some(51.50471), items[0].astrometric_ra);
+        assert_eq!(Some(-3.16406), items[0].astrometric_dec);
+        assert_eq!(None, items[0].apparent_dec);
+    }
+
+    #[test]
+    fn skipping_uncertainty_columns() {
+        let data = " Date__(UT)__HR:MN, , ,R.A._(ICRF),DEC__(ICRF),RA_3sigma,DEC_3sigma,
+$$SOE
+ 2022-Aug-13 00:00, , ,  51.50471,  -3.16406,  0.123,  0.456,
+$$EOE";
+        let items = EphemerisObserverParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(Some(51.50471), items[0].astrometric_ra);
+        assert_eq!(Some(-3.16406), items[0].astrometric_dec);
+
+        let data = " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC  RA_3sigma DEC_3sigma
+$$SOE
+ 2022-Aug-13 00:00     51.50471 -3.16406      0.123      0.456
+$$EOE";
+        let items = EphemerisObserverParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(Some(51.50471), items[0].astrometric_ra);
+        assert_eq!(Some(-3.16406), items[0].astrometric_dec);
+        assert_eq!(None, items[0].apparent_dec);
+    }
+
+    #[test]
+    fn parsing_topocentric_table() {
+        let data =
+            " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC  Azi____(a-app)___Elev  a-mass mag_ex
+$$SOE
+ 2022-Aug-13 03:00 Am  03 27 07.75 +18 13 13.1   70.984216 -11.802645    n.a.   n.a.
+ 2022-Aug-13 04:00 Cmr 03 27 15.61 +18 13 36.6    78.126453 -0.432541    n.a.   n.a.
+ 2022-Aug-13 05:00 *m  03 27 23.47 +18 14 00.1    85.553110 10.873012   5.258  1.065
+$$EOE";
+        let items = EphemerisObserverParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(SolarPresence::AstronomicalTwilight, items[0].solar_presence);
+        assert_eq!(LunarPresence::AboveHorizon, items[0].lunar_presence);
+        assert_eq!(None, items[0].event);
+        assert_eq!(Some(-11.802645), items[0].elevation);
+        assert_eq!(None, items[0].airmass);
+
+        assert_eq!(SolarPresence::CivilTwilight, items[1].solar_presence);
+        assert_eq!(Some(TargetEvent::Rise), items[1].event);
+        assert_eq!(Some(78.126453), items[1].azimuth);
+
+        assert_eq!(SolarPresence::Daylight, items[2].solar_presence);
+        assert_eq!(Some(5.258), items[2].airmass);
+        assert_eq!(Some(1.065), items[2].magnitude_extinction);
+
+        let visible = |min_elevation, max_solar_presence| {
+            items
+                .iter()
+                .filter(|item| item.is_visible(min_elevation, max_solar_presence))
+                .count()
+        };
+        assert_eq!(1, visible(5.0, SolarPresence::Daylight));
+        assert_eq!(1, visible(-20.0, SolarPresence::NauticalTwilight));
+        assert_eq!(0, visible(0.0, SolarPresence::CivilTwilight));
+
+        let data = " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC
+$$SOE
+ 2022-Aug-13 03:00 X   03 27 07.75 +18 13 13.1
+$$EOE";
+        assert!(matches!(
+            EphemerisObserverParser::parse(data.lines()).next(),
+            Some(Err(Error::Parse {
+                line: 3,
+                reason: ParseError::Expected("solar presence"),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn parsing_illumination_and_magnitudes() {
+        let data = " Date__(UT)__HR:MN      APmag   S-brt     Illu%     S-O-T /r     S-T-O
+$$SOE
+ 2022-Aug-13 00:00     -0.159   4.268  88.47542   87.1813 /L   39.8011
+ 2022-Aug-14 00:00       n.a.    n.a.  88.62124   87.7254 /?   39.5632
+$$EOE";
+        let items = EphemerisObserverParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(Some(-0.159), items[0].apparent_magnitude);
+        assert_eq!(Some(4.268), items[0].surface_brightness);
+        assert_eq!(Some(88.47542), items[0].illumination);
+        assert_eq!(Some(87.1813), items[0].solar_elongation);
+        assert_eq!(
+            Some(SolarElongationSide::Leading),
+            items[0].solar_elongation_side
+        );
+        assert_eq!(Some(39.8011), items[0].phase_angle);
+        assert_eq!(None, items[1].apparent_magnitude);
+        assert_eq!(None, items[1].solar_elongation_side);
+
+        // Comets list total and nuclear magnitudes instead.
+        let data =
+            " Date__(UT)__HR:MN, , ,   T-mag,   N-mag,    S-O-T,/r,      phi,  PAB-LON,  PAB-LAT,
+$$SOE
+ 2022-Aug-13 00:00, , ,  13.271,  16.735,  94.5337,/T,  30.2164, 333.4571,  -4.1207,
+$$EOE";
+        let items = EphemerisObserverParser::parse(data.lines())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(None, items[0].apparent_magnitude);
+        assert_eq!(Some(13.271), items[0].total_magnitude);
+        assert_eq!(Some(16.735), items[0].nuclear_magnitude);
+        assert_eq!(Some(94.5337), items[0].solar_elongation);
+        assert_eq!(
+            Some(SolarElongationSide::Trailing),
+            items[0].solar_elongation_side
+        );
+        assert_eq!(Some(30.2164), items[0].phase_angle);
+    }
+
+    #[test]
+    fn error_cases_when_parsing_observer_table() {
+        let data = "$$SOE\n 2022-Aug-13 00:00\n$$EOE";
+        assert!(matches!(
+            EphemerisObserverParser::parse(data.lines()).next(),
+            Some(Err(Error::Parse {
+                line: 1,
+                reason: ParseError::Expected("Date__"),
+                ..
+            }))
+        ));
+
+        let data = " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC
+$$SOE
+ 2022-Aug-13 00:00     03 28 22.74 +18 16
+ 2022-Aug-13 25:00     03 28 22.74 +18 16 57.2
+$$EOE";
+        let items: Vec<_> = EphemerisObserverParser::parse(data.lines()).collect();
+        assert!(matches!(
+            items[..],
+            [Err(Error::Parse {
+                line: 3,
+                reason: ParseError::Expected("angle"),
+                ..
+            })]
+        ));
     }
 }
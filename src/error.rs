@@ -0,0 +1,124 @@
+use std::num::ParseFloatError;
+
+use thiserror::Error;
+
+/// Everything that can go wrong while querying Horizons and reading its
+/// output.
+///
+/// New variants may be added in the future, so a `match` on it needs a
+/// wildcard arm.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Horizons could not be reached, e.g. because of a connection failure or
+    /// a timeout.
+    #[error("could not reach Horizons")]
+    Transport(#[source] reqwest::Error),
+
+    /// Horizons responded with an unsuccessful HTTP status.
+    #[error("Horizons responded with HTTP status {0}")]
+    HttpStatus(reqwest::StatusCode),
+
+    /// The response is not the JSON document Horizons API is supposed to
+    /// return.
+    #[error("could not decode Horizons response")]
+    Json(#[source] serde_json::Error),
+
+    /// Horizons reported an error instead of returning the result, either
+    /// in the `error` field of the response or as a message in place of the
+    /// ephemeris.
+    #[error("error returned from Horizons: {0}")]
+    Horizons(String),
+
+    /// Horizons does not know the target or the center body.
+    #[error("unknown body: {0}")]
+    UnknownBody(String),
+
+    /// Requested time span is not covered by the ephemeris of the target.
+    #[error("no ephemeris available: {0}")]
+    NoEphemeris(String),
+
+    /// Horizons rejected the query parameters, e.g. the step size or dates.
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// Horizons reported a different site than the one given by the query,
+    /// e.g. because the coordinates are out of range.
+    #[error("ephemeris computed for site {reported:?} instead of {requested:?}")]
+    SiteMismatch {
+        /// Coordinates of the site given by the query.
+        requested: [f64; 3],
+        /// Coordinates of the site in the header of the ephemeris.
+        reported: [f64; 3],
+    },
+
+    /// The query failed on every attempt. The error of the last one is kept.
+    #[error("query failed after {attempts} attempts")]
+    RetriesExhausted {
+        /// How many times the query was sent.
+        attempts: u32,
+        /// Why the last attempt failed.
+        #[source]
+        last: Box<Error>,
+    },
+
+    /// Saved Horizons output could not be read.
+    #[error("could not read Horizons output")]
+    Io(#[source] std::io::Error),
+
+    /// Horizons output could not be parsed.
+    #[error("could not parse line {line}: {text:?}")]
+    Parse {
+        /// Number of the offending line, counting from 1.
+        line: usize,
+        /// The offending line itself.
+        text: String,
+        /// What exactly is wrong with the line.
+        #[source]
+        reason: ParseError,
+    },
+}
+
+impl Error {
+    /// Whether repeating the query might succeed. Network problems, server
+    /// errors and garbled responses are transient, while errors reported by
+    /// Horizons itself will be the same every time the query is sent.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Transport(err) => !err.is_builder(),
+            Error::Json(_) => true,
+            Error::HttpStatus(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Error::Horizons(_)
+            | Error::UnknownBody(_)
+            | Error::NoEphemeris(_)
+            | Error::InvalidQuery(_)
+            | Error::SiteMismatch { .. }
+            | Error::RetriesExhausted { .. }
+            | Error::Io(_)
+            | Error::Parse { .. } => false,
+        }
+    }
+}
+
+/// Reason why a line of Horizons output could not be parsed. See
+/// [`Error::Parse`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Line does not contain expected label.
+    #[error("expected {0:?}")]
+    Expected(&'static str),
+
+    /// Value is not a valid number.
+    #[error("invalid number")]
+    InvalidNumber(#[source] ParseFloatError),
+
+    /// Value is not a valid date and time.
+    #[error("invalid date and time")]
+    InvalidDateTime(#[source] chrono::ParseError),
+
+    /// Input ended in the middle of an entry.
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+}
@@ -1,11 +1,33 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+mod body_id;
 mod client;
 mod ephemeris;
+mod error;
 mod major_bodies;
+mod metadata;
+pub mod parse;
+mod query;
+mod table;
+mod time;
 mod utilities;
 
-pub use client::{ephemeris_orbital_elements, ephemeris_vector, major_bodies};
-pub use ephemeris::{EphemerisOrbitalElementsItem, EphemerisVectorItem};
-pub use major_bodies::MajorBody;
+pub use body_id::{BodyId, BodyIdParseError};
+pub use client::{
+    ephemeris_observer, ephemeris_orbital_elements, ephemeris_vector, major_bodies, Backoff,
+    HorizonsClient, HorizonsClientBuilder, DEFAULT_BASE_URL,
+};
+pub use ephemeris::{
+    Ephemeris, EphemerisObserverItem, EphemerisOrbitalElementsItem, EphemerisTableItem,
+    EphemerisVectorItem, LunarPresence, RiseTransitSet, SolarElongationSide, SolarPresence,
+    TargetEvent,
+};
+pub use error::{Error, ParseError};
+pub use major_bodies::{BodyKind, MajorBody};
+pub use metadata::{EphemerisBody, EphemerisMetadata, ReferenceFrame};
+pub use query::{
+    EphemerisQuery, Epoch, ObserverQuantity, ObserverSite, OutputUnits, ReferencePlane,
+    ReferenceSystem, RiseSetHorizon, StepSize, VectorCorrection, VectorTable,
+};
+pub use time::{JulianDate, TimeScale};
@@ -1,4 +1,4 @@
-use crate::utilities::take_or_empty;
+use crate::{body_id::BodyId, utilities::take_or_empty};
 use std::num::ParseIntError;
 use thiserror::Error;
 
@@ -9,18 +9,65 @@ use thiserror::Error;
 ///
 /// Example
 /// ```
-/// # use rhorizons::MajorBody;
+/// # use rhorizons::{BodyId, MajorBody};
 /// let mb = MajorBody {
-///     id: 399,
-///     name: "Earth".to_string()
+///     id: BodyId::EARTH,
+///     name: "Earth".to_string(),
+///     designation: None,
+///     aliases: Some("Geocenter".to_string()),
 /// };
 /// ```
 #[derive(Debug, PartialEq, Eq)]
 pub struct MajorBody {
     /// Id of the major body
-    pub id: i32,
+    pub id: BodyId,
     /// Name of the major body (e.g. Earth)
     pub name: String,
+    /// Designation, e.g. `WE0913A` for a spacecraft
+    pub designation: Option<String>,
+    /// IAU number, aliases or other names, e.g. `SSB` or COSPAR ID of a
+    /// spacecraft
+    pub aliases: Option<String>,
+}
+
+/// Kind of a major body, following the structure of Horizons identifiers.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#defs>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyKind {
+    /// Solar System Barycenter, `0`.
+    SolarSystemBarycenter,
+    /// Barycenter of a planetary system, from `1` for Mercury to `9` for
+    /// Pluto.
+    Barycenter,
+    /// The Sun, `10`.
+    Sun,
+    /// Planet, `x99`, e.g. `399` for the Earth.
+    Planet,
+    /// Natural satellite, from `x01` to `x98`, e.g. `301` for the Moon, or
+    /// `x5nnn` for some of the recently discovered ones, e.g. `55501`.
+    Satellite,
+    /// Spacecraft, identified by a negative number.
+    Spacecraft,
+    /// Anything else, e.g. Lagrange points.
+    Other,
+}
+
+impl BodyKind {
+    /// Kind of the body with given Horizons identifier.
+    pub fn of(id: BodyId) -> Self {
+        let BodyId(id) = id;
+        match id {
+            i32::MIN..=-1 => BodyKind::Spacecraft,
+            0 => BodyKind::SolarSystemBarycenter,
+            1..=9 => BodyKind::Barycenter,
+            10 => BodyKind::Sun,
+            100..=999 if id % 100 == 99 => BodyKind::Planet,
+            100..=999 if id % 100 != 0 => BodyKind::Satellite,
+            10_000..=99_999 => BodyKind::Satellite,
+            _ => BodyKind::Other,
+        }
+    }
 }
 
 #[derive(Error, Debug, PartialEq, Eq)]
@@ -29,24 +76,114 @@ pub enum MajorBodyParseError {
     InvalidId(#[source] ParseIntError),
 }
 
-impl TryFrom<&str> for MajorBody {
-    type Error = MajorBodyParseError;
+/// Where the columns of the major bodies table end. The last column, IAU
+/// number and aliases, takes the rest of the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MajorBodyColumns {
+    id: usize,
+    name: usize,
+    designation: usize,
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        // It seems (yes, I haven't found any specs) that Horizons formats its
-        // result as fixed sized tables. Even if name exceeds the size of the
-        // column, it gets truncated.
+impl Default for MajorBodyColumns {
+    /// Widths used by Horizons at the time of writing.
+    fn default() -> Self {
+        Self {
+            id: 9,
+            name: 45,
+            designation: 57,
+        }
+    }
+}
+
+impl MajorBodyColumns {
+    /// Read the columns from the line underlining the header, e.g.
+    /// `  -------  ---------------------------------- -----------  -------------------`.
+    /// A column spans from the end of the previous run of dashes to the end
+    /// of its own.
+    pub(crate) fn from_header(line: &str) -> Option<Self> {
+        if line.trim().is_empty() || !line.chars().all(|c| c == '-' || c == ' ') {
+            return None;
+        }
+
+        let mut ends = line
+            .char_indices()
+            .zip(line.chars().skip(1).chain([' ']))
+            .filter(|((_, c), next)| *c == '-' && *next != '-')
+            .map(|((index, _), _)| index + 1);
+
+        Some(Self {
+            id: ends.next()?,
+            name: ends.next()?,
+            designation: ends.next()?,
+        })
+    }
+}
+
+impl MajorBody {
+    /// Kind of the body, told by its identifier.
+    pub fn kind(&self) -> BodyKind {
+        self.id.kind()
+    }
+
+    /// Barycenter of the planetary system the body belongs to, from `1` for
+    /// Mercury to `9` for Pluto. `None` for bodies outside of planetary
+    /// systems, e.g. the Sun or spacecraft.
+    ///
+    /// Example
+    /// ```
+    /// # use rhorizons::{BodyId, BodyKind, MajorBody};
+    /// # fn jovian_moons(bodies: &[MajorBody]) -> Vec<&MajorBody> {
+    /// bodies
+    ///     .iter()
+    ///     .filter(|body| {
+    ///         body.kind() == BodyKind::Satellite
+    ///             && body.parent_system() == Some(BodyId::JUPITER_BARYCENTER)
+    ///     })
+    ///     .collect()
+    /// # }
+    /// ```
+    pub fn parent_system(&self) -> Option<BodyId> {
+        self.id.parent_system()
+    }
+
+    /// Planet of a satellite, or the planet itself, e.g. `599` for any of
+    /// Jupiter's moons.
+    pub fn parent_planet(&self) -> Option<BodyId> {
+        self.id.parent_planet()
+    }
 
-        let (id, value) = take_or_empty(value, 9);
-        let (name, _) = take_or_empty(value, 35);
+    /// Parse a row of the major bodies table with given columns.
+    pub(crate) fn parse(
+        value: &str,
+        columns: &MajorBodyColumns,
+    ) -> Result<Self, MajorBodyParseError> {
+        let (id, value) = take_or_empty(value, columns.id);
+        let (name, value) = take_or_empty(value, columns.name - columns.id);
+        let (designation, aliases) = take_or_empty(value, columns.designation - columns.name);
+
+        let non_empty = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
 
         Ok(Self {
-            id: id.trim().parse().map_err(MajorBodyParseError::InvalidId)?,
+            id: BodyId(id.trim().parse().map_err(MajorBodyParseError::InvalidId)?),
             name: name.trim().to_string(),
+            designation: non_empty(designation),
+            aliases: non_empty(aliases),
         })
     }
 }
 
+impl TryFrom<&str> for MajorBody {
+    type Error = MajorBodyParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        // Horizons formats its result as fixed sized tables. Even if name
+        // exceeds the size of the column, it gets truncated. Without the
+        // header at hand, assume the usual column widths.
+        MajorBody::parse(value, &MajorBodyColumns::default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -56,8 +193,10 @@ mod tests {
     fn reading_major_bodies() {
         assert_eq!(
             MajorBody {
-                id: 0,
-                name: "Solar System Barycenter".to_string()
+                id: BodyId::SOLAR_SYSTEM_BARYCENTER,
+                name: "Solar System Barycenter".to_string(),
+                designation: None,
+                aliases: Some("SSB".to_string()),
             },
             MajorBody::try_from("        0  Solar System Barycenter                         SSB")
                 .unwrap()
@@ -65,16 +204,20 @@ mod tests {
 
         assert_eq!(
             MajorBody {
-                id: 699,
-                name: "Saturn".to_string()
+                id: BodyId::SATURN,
+                name: "Saturn".to_string(),
+                designation: None,
+                aliases: None,
             },
             MajorBody::try_from("      699  Saturn").unwrap()
         );
 
         assert_eq!(
             MajorBody {
-                id: -78000,
-                name: "Chang'e_5-T1_booster (spacecraft)".to_string()
+                id: BodyId(-78000),
+                name: "Chang'e_5-T1_booster (spacecraft)".to_string(),
+                designation: Some("WE0913A".to_string()),
+                aliases: Some("2014-065B".to_string()),
             },
             MajorBody::try_from(
                 "  -78000  Chang'e_5-T1_booster (spacecraft)  WE0913A      2014-065B"
@@ -83,6 +226,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reading_columns_from_header() {
+        assert_eq!(
+            Some(MajorBodyColumns::default()),
+            MajorBodyColumns::from_header(
+                "  -------  ---------------------------------- -----------  -------------------"
+            )
+        );
+
+        let columns = MajorBodyColumns::from_header("  ----- ---------- ---- ------").unwrap();
+        assert_eq!(
+            MajorBody {
+                id: BodyId::MOON,
+                name: "Moon".to_string(),
+                designation: Some("X".to_string()),
+                aliases: Some("Luna".to_string()),
+            },
+            MajorBody::parse("    301 Moon       X    Luna", &columns).unwrap()
+        );
+
+        assert_eq!(None, MajorBodyColumns::from_header("  ID#      Name"));
+        assert_eq!(None, MajorBodyColumns::from_header("  -------  -----"));
+        assert_eq!(None, MajorBodyColumns::from_header(""));
+    }
+
+    #[test]
+    fn classifying_bodies() {
+        let kinds = [
+            (0, BodyKind::SolarSystemBarycenter, None, None),
+            (3, BodyKind::Barycenter, Some(3), None),
+            (10, BodyKind::Sun, None, None),
+            (199, BodyKind::Planet, Some(1), Some(199)),
+            (399, BodyKind::Planet, Some(3), Some(399)),
+            (999, BodyKind::Planet, Some(9), Some(999)),
+            (301, BodyKind::Satellite, Some(3), Some(399)),
+            (501, BodyKind::Satellite, Some(5), Some(599)),
+            (55501, BodyKind::Satellite, Some(5), Some(599)),
+            (-78000, BodyKind::Spacecraft, None, None),
+            (31, BodyKind::Other, None, None),
+            (300, BodyKind::Other, None, None),
+        ];
+
+        for (id, kind, system, planet) in kinds {
+            let body = MajorBody {
+                id: BodyId(id),
+                name: String::new(),
+                designation: None,
+                aliases: None,
+            };
+            assert_eq!(kind, body.kind(), "{}", id);
+            assert_eq!(system.map(BodyId), body.parent_system(), "{}", id);
+            assert_eq!(planet.map(BodyId), body.parent_planet(), "{}", id);
+        }
+    }
+
     #[test]
     fn error_cases_when_parsing_major_bodies() {
         assert!(matches!(
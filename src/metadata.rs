@@ -0,0 +1,324 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    body_id::BodyId,
+    ephemeris::parse_calendar_time,
+    error::ParseError,
+    query::{OutputUnits, ReferencePlane, ReferenceSystem, VectorCorrection},
+    time::TimeScale,
+};
+
+/// Reference frame of positions, velocities and orbital elements, as
+/// reported by Horizons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReferenceFrame {
+    /// Reference plane, e.g. the ecliptic.
+    pub plane: ReferencePlane,
+    /// Reference system, e.g. the ICRF.
+    pub system: ReferenceSystem,
+}
+
+impl ReferenceFrame {
+    /// Parse the value of the `Reference frame` header line, e.g.
+    /// `Ecliptic of J2000.0`, `ICRF` or `FK4/B1950.0`. `None` for frames
+    /// this crate doesn't know.
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.to_lowercase();
+
+        let plane = if value.contains("ecliptic") {
+            ReferencePlane::Ecliptic
+        } else if value.contains("equator") {
+            ReferencePlane::BodyEquator
+        } else if value.starts_with("icrf") || value.starts_with("fk4") {
+            ReferencePlane::Frame
+        } else {
+            return None;
+        };
+        let system = if value.contains("b1950") {
+            ReferenceSystem::B1950
+        } else {
+            ReferenceSystem::Icrf
+        };
+
+        Some(Self { plane, system })
+    }
+}
+
+/// Target or center body, as named in the header of an ephemeris, e.g.
+/// `Earth (399)                     {source: DE441}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EphemerisBody {
+    /// Name of the body, e.g. `Earth`.
+    pub name: String,
+    /// Horizons identifier of the body, when given as a number.
+    pub id: Option<BodyId>,
+    /// Source of the ephemeris of the body, e.g. `DE441`.
+    pub source: Option<String>,
+}
+
+impl EphemerisBody {
+    /// Parse the value of the `Target body name` or `Center body name` header
+    /// line.
+    fn parse(value: &str) -> Self {
+        let (body, source) = match value.split_once('{') {
+            Some((body, source)) => (
+                body,
+                source
+                    .trim_end_matches('}')
+                    .trim()
+                    .strip_prefix("source:")
+                    .map(|source| source.trim().to_string()),
+            ),
+            None => (value, None),
+        };
+        let body = body.trim();
+
+        // Small bodies are followed by their designation instead of a number.
+        let id_suffix = body
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once(" ("))
+            .and_then(|(name, id)| Some((name, BodyId(id.parse().ok()?))));
+        let (name, id) = match id_suffix {
+            Some((name, id)) => (name, Some(id)),
+            None => (body, None),
+        };
+
+        Self {
+            name: name.to_string(),
+            id,
+            source,
+        }
+    }
+}
+
+/// Description of an ephemeris, read from the header preceding its entries.
+/// Quantities missing from the header, or given in a form this crate doesn't
+/// know, are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EphemerisMetadata {
+    /// Body the ephemeris is computed for.
+    pub target: Option<EphemerisBody>,
+    /// Body at the origin of the coordinates.
+    pub center: Option<EphemerisBody>,
+    /// Site on the center body, e.g. `BODY CENTER`.
+    pub center_site: Option<String>,
+    /// Geodetic coordinates of the center site: east longitude and latitude
+    /// in degrees, altitude in km.
+    pub center_geodetic: Option<[f64; 3]>,
+    /// Cylindrical coordinates of the center site: east longitude in degrees,
+    /// distance from the spin axis and from the equatorial plane in km.
+    pub center_cylindric: Option<[f64; 3]>,
+    /// Radii of the center body in km: two equatorial and the polar one.
+    pub center_radii: Option<[f64; 3]>,
+    /// Gravitational parameter of the center body used for orbital elements,
+    /// in km^3/s^2 or the equivalent in other output units.
+    pub keplerian_gm: Option<f64>,
+
+    /// Time of the first entry of a time span.
+    pub start_time: Option<DateTime<Utc>>,
+    /// Time of the last entry of a time span.
+    pub stop_time: Option<DateTime<Utc>>,
+    /// Interval between the entries as printed by Horizons, e.g. `60 minutes`.
+    pub step_size: Option<String>,
+
+    /// Frame of the coordinates.
+    pub reference_frame: Option<ReferenceFrame>,
+    /// Units of distance and time of the entries.
+    pub output_units: Option<OutputUnits>,
+    /// Aberration corrections applied to the entries.
+    pub vector_correction: Option<VectorCorrection>,
+}
+
+impl EphemerisMetadata {
+    /// Take note of a line of the header, ignoring lines that do not describe
+    /// the ephemeris. Values which cannot be read leave their quantity `None`.
+    pub(crate) fn read_header_line(&mut self, line: &str) {
+        let Some((label, value)) = line.split_once(':') else {
+            return;
+        };
+        let value = value.trim();
+
+        match label.trim_end() {
+            "Target body name" => self.target = Some(EphemerisBody::parse(value)),
+            "Center body name" => self.center = Some(EphemerisBody::parse(value)),
+            "Center-site name" => self.center_site = Some(value.to_string()),
+            "Center geodetic" => self.center_geodetic = parse_triple(value).ok(),
+            "Center cylindric" => self.center_cylindric = parse_triple(value).ok(),
+            "Center radii" => self.center_radii = parse_triple(value).ok(),
+            "Keplerian GM" => {
+                let gm = value.split_whitespace().next().unwrap_or_default();
+                self.keplerian_gm = gm.parse().ok();
+            }
+            "Start time" => self.start_time = parse_calendar_time(value, TimeScale::Tdb).ok(),
+            "Stop  time" => self.stop_time = parse_calendar_time(value, TimeScale::Tdb).ok(),
+            "Step-size" => self.step_size = Some(value.to_string()),
+            "Reference frame" => self.reference_frame = ReferenceFrame::parse(value),
+            // Elements list the units of angles and Tp as well, e.g.
+            // `KM-S, deg, Julian Day Number (Tp)`.
+            "Output units" => {
+                let units = value.split(',').next().unwrap_or_default().trim();
+                self.output_units = OutputUnits::from_parameter(units);
+            }
+            "Output type" => self.vector_correction = VectorCorrection::from_output_type(value),
+            _ => {}
+        }
+    }
+}
+
+/// Parse three comma separated numbers followed by their description, e.g.
+/// `696000.0, 696000.0, 696000.0 km {Equator_a, b, pole_c}`.
+fn parse_triple(value: &str) -> Result<[f64; 3], ParseError> {
+    let numbers = value.split('{').next().unwrap_or_default();
+    let numbers = numbers.trim().trim_end_matches("km").trim_end();
+
+    let mut triple = [0.0; 3];
+    let mut numbers = numbers.split(',');
+    for number in &mut triple {
+        *number = numbers
+            .next()
+            .ok_or(ParseError::Expected(","))?
+            .trim()
+            .parse()
+            .map_err(ParseError::InvalidNumber)?;
+    }
+    Ok(triple)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_reference_frames() {
+        let frames = [
+            (
+                "Ecliptic of J2000.0",
+                ReferencePlane::Ecliptic,
+                ReferenceSystem::Icrf,
+            ),
+            ("ICRF", ReferencePlane::Frame, ReferenceSystem::Icrf),
+            (
+                "Ecliptic of B1950.0",
+                ReferencePlane::Ecliptic,
+                ReferenceSystem::B1950,
+            ),
+            ("FK4/B1950.0", ReferencePlane::Frame, ReferenceSystem::B1950),
+            (
+                "Earth mean equator and node of date",
+                ReferencePlane::BodyEquator,
+                ReferenceSystem::Icrf,
+            ),
+        ];
+
+        for (value, plane, system) in frames {
+            assert_eq!(
+                Some(ReferenceFrame { plane, system }),
+                ReferenceFrame::parse(value)
+            );
+        }
+        assert_eq!(None, ReferenceFrame::parse("Galactic"));
+    }
+
+    #[test]
+    fn parsing_bodies() {
+        assert_eq!(
+            EphemerisBody {
+                name: "Earth".to_string(),
+                id: Some(BodyId::EARTH),
+                source: Some("DE441".to_string()),
+            },
+            EphemerisBody::parse("Earth (399)                     {source: DE441}")
+        );
+        assert_eq!(
+            EphemerisBody {
+                name: "1 Ceres (A801 AA)".to_string(),
+                id: None,
+                source: Some("JPL#48".to_string()),
+            },
+            EphemerisBody::parse("1 Ceres (A801 AA)               {source: JPL#48}")
+        );
+        assert_eq!(
+            EphemerisBody {
+                name: "Sun".to_string(),
+                id: Some(BodyId::SUN),
+                source: None,
+            },
+            EphemerisBody::parse("Sun (10)")
+        );
+    }
+
+    #[test]
+    fn parsing_triples() {
+        assert_eq!(
+            Ok([696000.0, 696000.0, 696000.0]),
+            parse_triple("696000.0, 696000.0, 696000.0 km {Equator_a, b, pole_c}")
+        );
+        assert_eq!(
+            Ok([243.1104, 35.4267, 1.0]),
+            parse_triple("243.1104, 35.4267, 1.0  {E-lon(deg),Lat(deg),Alt(km)}")
+        );
+        assert_eq!(Err(ParseError::Expected(",")), parse_triple("0.0, 0.0"));
+        assert!(matches!(
+            parse_triple("0.0, x, 0.0"),
+            Err(ParseError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn reading_header_lines() {
+        let mut metadata = EphemerisMetadata::default();
+        metadata.read_header_line("Output type     : GEOMETRIC cartesian states");
+        metadata.read_header_line("JDTDB");
+        assert_eq!(None, metadata.reference_frame);
+        assert_eq!(
+            Some(VectorCorrection::Geometric),
+            metadata.vector_correction
+        );
+
+        metadata.read_header_line("Reference frame : Ecliptic of J2000.0");
+        assert_eq!(
+            Some(ReferenceFrame {
+                plane: ReferencePlane::Ecliptic,
+                system: ReferenceSystem::Icrf
+            }),
+            metadata.reference_frame
+        );
+
+        metadata.read_header_line("Output units    : KM-S, deg, Julian Day Number (Tp)");
+        assert_eq!(Some(OutputUnits::KmS), metadata.output_units);
+        metadata.read_header_line("Output units    : AU-D");
+        assert_eq!(Some(OutputUnits::AuD), metadata.output_units);
+
+        // Unknown values are not guessed.
+        metadata.read_header_line("Output units    : furlongs");
+        assert_eq!(None, metadata.output_units);
+        metadata.read_header_line("Output type     : HELIOCENTRIC cartesian states");
+        assert_eq!(None, metadata.vector_correction);
+        metadata.read_header_line("Reference frame : Galactic");
+        assert_eq!(None, metadata.reference_frame);
+    }
+
+    #[test]
+    fn reading_unreadable_header_values() {
+        let mut metadata = EphemerisMetadata::default();
+        metadata.read_header_line("Start time      : A.D. 2022-Jun-19 18:00:00.0000 TDB");
+        metadata.read_header_line("Center radii    : 6378.137, 6378.137, 6356.752 km");
+        metadata.read_header_line("Keplerian GM    : 1.3271244004127942E+11 km^3/s^2");
+        assert!(metadata.start_time.is_some());
+        assert!(metadata.center_radii.is_some());
+        assert!(metadata.keplerian_gm.is_some());
+
+        metadata.read_header_line("Start time      : A.D. 2022-Jun-19");
+        metadata.read_header_line("Stop  time      : sometime");
+        metadata.read_header_line("Center geodetic : 0.0, 0.0");
+        metadata.read_header_line("Center cylindric: 0.0, x, 0.0");
+        metadata.read_header_line("Center radii    : unknown");
+        metadata.read_header_line("Keplerian GM    : n.a.");
+        assert_eq!(None, metadata.start_time);
+        assert_eq!(None, metadata.stop_time);
+        assert_eq!(None, metadata.center_geodetic);
+        assert_eq!(None, metadata.center_cylindric);
+        assert_eq!(None, metadata.center_radii);
+        assert_eq!(None, metadata.keplerian_gm);
+    }
+}
@@ -0,0 +1,313 @@
+//! Parsing of Horizons text output obtained elsewhere, e.g. saved from the
+//! web interface or received by email.
+//!
+//! Example
+//! ```no_run
+//! use rhorizons::parse::{self, ParsedEphemeris};
+//!
+//! # fn main() -> Result<(), rhorizons::Error> {
+//! match parse::from_path("horizons_results.txt")? {
+//!     ParsedEphemeris::Vectors(vectors) => println!("{} vectors", vectors.len()),
+//!     ParsedEphemeris::OrbitalElements(elements) => println!("{} elements", elements.len()),
+//!     ParsedEphemeris::Observer(observer) => println!("{} observations", observer.len()),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fs::File, io::Read, path::Path};
+
+use crate::{
+    ephemeris::{
+        Ephemeris, EphemerisObserverItem, EphemerisObserverParser, EphemerisOrbitalElementsItem,
+        EphemerisOrbitalElementsParser, EphemerisTableItem, EphemerisTableParser,
+        EphemerisVectorItem, EphemerisVectorParser,
+    },
+    error::{Error, ParseError},
+};
+
+/// Ephemeris of either kind, see [`from_str`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedEphemeris {
+    /// Vector table.
+    Vectors(Ephemeris<EphemerisVectorItem>),
+    /// Orbital elements table.
+    OrbitalElements(Ephemeris<EphemerisOrbitalElementsItem>),
+    /// Observer table.
+    Observer(Ephemeris<EphemerisObserverItem>),
+}
+
+/// Parse vector table from Horizons output.
+pub fn vectors_from_str(output: &str) -> Result<Ephemeris<EphemerisVectorItem>, Error> {
+    let lines: Vec<_> = output.lines().collect();
+    ensure_ephemeris(&lines)?;
+    vectors_from_lines(lines.into_iter())
+}
+
+/// Parse orbital elements table from Horizons output.
+pub fn orbital_elements_from_str(
+    output: &str,
+) -> Result<Ephemeris<EphemerisOrbitalElementsItem>, Error> {
+    let lines: Vec<_> = output.lines().collect();
+    ensure_ephemeris(&lines)?;
+    orbital_elements_from_lines(lines.into_iter())
+}
+
+/// Parse observer table from Horizons output.
+pub fn observer_from_str(output: &str) -> Result<Ephemeris<EphemerisObserverItem>, Error> {
+    let lines: Vec<_> = output.lines().collect();
+    ensure_ephemeris(&lines)?;
+    observer_from_lines(lines.into_iter())
+}
+
+/// Parse any table from Horizons output, keeping the values by the labels
+/// listed in its header.
+pub fn table_from_str(output: &str) -> Result<Ephemeris<EphemerisTableItem>, Error> {
+    let lines: Vec<_> = output.lines().collect();
+    ensure_ephemeris(&lines)?;
+
+    let mut parser = EphemerisTableParser::parse(lines.into_iter());
+    let items = (&mut parser).collect::<Result<_, _>>()?;
+    Ok(Ephemeris {
+        metadata: parser.into_metadata(),
+        items,
+    })
+}
+
+/// Parse Horizons output, telling vectors from orbital elements by the
+/// header or, if it's missing, by the first entry. Observer tables are told
+/// by the line naming their columns.
+pub fn from_str(output: &str) -> Result<ParsedEphemeris, Error> {
+    let lines: Vec<_> = output.lines().collect();
+    ensure_ephemeris(&lines)?;
+
+    if is_observer_table(&lines) {
+        Ok(ParsedEphemeris::Observer(observer_from_lines(
+            lines.into_iter(),
+        )?))
+    } else if is_vector_table(&lines)? {
+        Ok(ParsedEphemeris::Vectors(vectors_from_lines(
+            lines.into_iter(),
+        )?))
+    } else {
+        Ok(ParsedEphemeris::OrbitalElements(
+            orbital_elements_from_lines(lines.into_iter())?,
+        ))
+    }
+}
+
+/// Read and parse Horizons output, see [`from_str`].
+pub fn from_reader<R: Read>(mut reader: R) -> Result<ParsedEphemeris, Error> {
+    let mut output = String::new();
+    reader.read_to_string(&mut output).map_err(Error::Io)?;
+    from_str(&output)
+}
+
+/// Read and parse Horizons output saved in a file, see [`from_str`].
+pub fn from_path<P: AsRef<Path>>(path: P) -> Result<ParsedEphemeris, Error> {
+    from_reader(File::open(path).map_err(Error::Io)?)
+}
+
+/// Make sure that the output contains an ephemeris, pointing at the end of
+/// the output otherwise.
+fn ensure_ephemeris(lines: &[&str]) -> Result<(), Error> {
+    if lines.iter().any(|line| line.trim() == "$$SOE") {
+        Ok(())
+    } else {
+        Err(Error::Parse {
+            line: lines.len().max(1),
+            text: lines.last().copied().unwrap_or_default().to_owned(),
+            reason: ParseError::Expected("$$SOE"),
+        })
+    }
+}
+
+pub(crate) fn vectors_from_lines<'a>(
+    lines: impl Iterator<Item = &'a str>,
+) -> Result<Ephemeris<EphemerisVectorItem>, Error> {
+    let mut parser = EphemerisVectorParser::parse(lines);
+    let items = (&mut parser).collect::<Result<_, _>>()?;
+    Ok(Ephemeris {
+        metadata: parser.into_metadata(),
+        items,
+    })
+}
+
+pub(crate) fn orbital_elements_from_lines<'a>(
+    lines: impl Iterator<Item = &'a str>,
+) -> Result<Ephemeris<EphemerisOrbitalElementsItem>, Error> {
+    let mut parser = EphemerisOrbitalElementsParser::parse(lines);
+    let items = (&mut parser).collect::<Result<_, _>>()?;
+    Ok(Ephemeris {
+        metadata: parser.into_metadata(),
+        items,
+    })
+}
+
+pub(crate) fn observer_from_lines<'a>(
+    lines: impl Iterator<Item = &'a str>,
+) -> Result<Ephemeris<EphemerisObserverItem>, Error> {
+    let mut parser = EphemerisObserverParser::parse(lines);
+    let items = (&mut parser).collect::<Result<_, _>>()?;
+    Ok(Ephemeris {
+        metadata: parser.into_metadata(),
+        items,
+    })
+}
+
+/// Whether the output is an observer table, whose header names the columns,
+/// e.g. ` Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC`.
+fn is_observer_table(lines: &[&str]) -> bool {
+    lines
+        .iter()
+        .take_while(|line| line.trim() != "$$SOE")
+        .any(|line| line.trim_start().starts_with("Date_"))
+}
+
+/// Whether the output is a vector table rather than orbital elements. Lines
+/// are expected to contain `$$SOE`.
+fn is_vector_table(lines: &[&str]) -> Result<bool, Error> {
+    let soe = lines
+        .iter()
+        .position(|line| line.trim() == "$$SOE")
+        .unwrap_or(0);
+
+    // E.g. `GEOMETRIC cartesian states` or `GEOMETRIC osculating elements`.
+    let output_type = lines[..soe].iter().find_map(|line| {
+        let (label, value) = line.split_once(':')?;
+        (label.trim_end() == "Output type").then_some(value)
+    });
+    match output_type {
+        Some(output_type) if output_type.contains("cartesian") => return Ok(true),
+        Some(output_type) if output_type.contains("elements") => return Ok(false),
+        _ => {}
+    }
+
+    // Labels of the line following the date of the first entry, or the last
+    // line of a shorter output.
+    let (index, line) = match lines.get(soe + 2) {
+        Some(line) => (soe + 2, *line),
+        None => (
+            lines.len().saturating_sub(1),
+            lines.last().copied().unwrap_or_default(),
+        ),
+    };
+    if [" X =", " VX=", " LT="]
+        .iter()
+        .any(|label| line.starts_with(label))
+    {
+        Ok(true)
+    } else if line.starts_with(" EC=") {
+        Ok(false)
+    } else {
+        Err(Error::Parse {
+            line: index + 1,
+            text: line.to_owned(),
+            reason: ParseError::Expected("vectors or orbital elements"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detecting_table_type() {
+        let vectors = include_str!("vector.txt");
+        let elements = include_str!("orbital_elements.txt");
+
+        assert!(matches!(
+            from_str(vectors),
+            Ok(ParsedEphemeris::Vectors(ephemeris)) if ephemeris.len() == 4
+        ));
+        assert!(matches!(
+            from_str(elements),
+            Ok(ParsedEphemeris::OrbitalElements(ephemeris)) if ephemeris.len() == 4
+        ));
+        assert!(matches!(
+            from_str(include_str!("observer.txt")),
+            Ok(ParsedEphemeris::Observer(ephemeris)) if ephemeris.len() == 4
+        ));
+
+        // Without the header, the first entry tells.
+        let headless = &elements[elements.find("$$SOE").unwrap()..];
+        assert!(matches!(
+            from_str(headless),
+            Ok(ParsedEphemeris::OrbitalElements(_))
+        ));
+
+        assert!(matches!(
+            from_str("$$SOE\n$$EOE"),
+            Err(Error::Parse {
+                line: 2,
+                reason: ParseError::Expected("vectors or orbital elements"),
+                ..
+            })
+        ));
+        assert!(matches!(
+            from_str("$$SOE\n2459750.250000000 = A.D. 2022-Jun-19 18:00:00.0000 TDB\n Q =\n$$EOE"),
+            Err(Error::Parse {
+                line: 3,
+                reason: ParseError::Expected("vectors or orbital elements"),
+                ..
+            })
+        ));
+
+        // Output without an ephemeris is not blamed on Horizons.
+        assert!(matches!(
+            from_str(" No ephemeris for target \"Earth\"\n"),
+            Err(Error::Parse {
+                line: 1,
+                reason: ParseError::Expected("$$SOE"),
+                ..
+            })
+        ));
+        assert!(matches!(
+            vectors_from_str(""),
+            Err(Error::Parse {
+                line: 1,
+                reason: ParseError::Expected("$$SOE"),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reading_any_table() {
+        let ephemeris = table_from_str(include_str!("orbital_elements.txt")).unwrap();
+        assert_eq!(4, ephemeris.len());
+        assert_eq!(12, ephemeris[0].values.len());
+        assert_eq!(Some(&1.711794334680415E-02), ephemeris[0].values.get("EC"));
+    }
+
+    #[test]
+    fn reading_saved_output() {
+        // Files saved on Windows or received by email.
+        let vectors = include_str!("vector.txt").replace('\n', "\r\n");
+        let ephemeris = vectors_from_str(&vectors).unwrap();
+        assert_eq!(4, ephemeris.len());
+        assert!(ephemeris.metadata.reference_frame.is_some());
+
+        assert!(matches!(
+            from_reader(include_str!("orbital_elements.txt").as_bytes()),
+            Ok(ParsedEphemeris::OrbitalElements(_))
+        ));
+        assert!(matches!(from_path("does/not/exist.txt"), Err(Error::Io(_))));
+
+        // Markers followed by whitespace, e.g. after copying from a browser.
+        let padded = |output: &str| {
+            output
+                .replace("$$SOE", "$$SOE  ")
+                .replace("$$EOE", "$$EOE\t")
+        };
+        assert!(matches!(
+            from_str(&padded(include_str!("vector.txt"))),
+            Ok(ParsedEphemeris::Vectors(ephemeris)) if ephemeris.len() == 4
+        ));
+        assert!(matches!(
+            from_str(&padded(include_str!("observer.txt"))),
+            Ok(ParsedEphemeris::Observer(ephemeris)) if ephemeris.len() == 4
+        ));
+    }
+}
@@ -0,0 +1,1205 @@
+use chrono::{DateTime, Duration, DurationRound, NaiveDateTime, Utc};
+
+use crate::{
+    body_id::BodyId,
+    ephemeris::{EphemerisObserverItem, SolarPresence},
+    error::Error,
+    metadata::EphemerisMetadata,
+    time::{JulianDate, TimeScale},
+};
+
+/// Origin of the coordinates of an ephemeris, known in Horizons as `CENTER`.
+/// For observer ephemerides, it is the place of the observer.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#center>
+///
+/// Example
+/// ```
+/// # use rhorizons::{BodyId, ObserverSite};
+/// // Geocentric.
+/// let site = ObserverSite::BodyCenter(BodyId::EARTH);
+/// // Solar System Barycenter.
+/// let site = ObserverSite::SOLAR_SYSTEM_BARYCENTER;
+/// // Mauna Kea, by its MPC observatory code.
+/// let site = ObserverSite::Observatory {
+///     code: "568".to_string(),
+///     body: BodyId::EARTH,
+/// };
+/// // Goldstone Deep Space Communications Complex.
+/// let site = ObserverSite::Geodetic {
+///     body: BodyId::EARTH,
+///     longitude: 243.1104,
+///     latitude: 35.4267,
+///     altitude: 1.0,
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObserverSite {
+    /// Center of a body, e.g. geocentric, or a barycenter.
+    BodyCenter(BodyId),
+
+    /// Observatory given by its code, e.g. `568` on the Earth, as assigned by
+    /// the Minor Planet Center.
+    Observatory {
+        /// Observatory code.
+        code: String,
+        /// Body on which the observatory is located.
+        body: BodyId,
+    },
+
+    /// Site on a body given by its geodetic coordinates.
+    Geodetic {
+        /// Body on which the site is located.
+        body: BodyId,
+        /// East longitude in degrees.
+        longitude: f64,
+        /// Latitude in degrees.
+        latitude: f64,
+        /// Altitude above the reference ellipsoid in km.
+        altitude: f64,
+    },
+
+    /// Site on a body given by its cylindrical coordinates.
+    Cylindrical {
+        /// Body on which the site is located.
+        body: BodyId,
+        /// East longitude in degrees.
+        longitude: f64,
+        /// Distance from the spin axis in km.
+        distance_from_axis: f64,
+        /// Distance from the equatorial plane in km, positive to the north.
+        distance_from_equator: f64,
+    },
+}
+
+impl ObserverSite {
+    /// Center of the Sun. Note that Solar System Barycenter is in a slightly
+    /// different place.
+    ///
+    /// <https://astronomy.stackexchange.com/questions/44851/>
+    pub const SUN: ObserverSite = ObserverSite::BodyCenter(BodyId::SUN);
+
+    /// Center of mass of the whole Solar System.
+    pub const SOLAR_SYSTEM_BARYCENTER: ObserverSite =
+        ObserverSite::BodyCenter(BodyId::SOLAR_SYSTEM_BARYCENTER);
+
+    /// Parameters selecting this site in a query.
+    fn parameters(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ObserverSite::BodyCenter(body) => vec![("CENTER", body.center())],
+            ObserverSite::Observatory { code, body } => {
+                vec![("CENTER", format!("{}@{}", code, body))]
+            }
+            ObserverSite::Geodetic {
+                body,
+                longitude,
+                latitude,
+                altitude,
+            } => vec![
+                ("CENTER", format!("coord@{}", body)),
+                ("COORD_TYPE", "GEODETIC".to_string()),
+                (
+                    "SITE_COORD",
+                    format!("'{},{},{}'", longitude, latitude, altitude),
+                ),
+            ],
+            ObserverSite::Cylindrical {
+                body,
+                longitude,
+                distance_from_axis,
+                distance_from_equator,
+            } => vec![
+                ("CENTER", format!("coord@{}", body)),
+                ("COORD_TYPE", "CYLINDRICAL".to_string()),
+                (
+                    "SITE_COORD",
+                    format!(
+                        "'{},{},{}'",
+                        longitude, distance_from_axis, distance_from_equator
+                    ),
+                ),
+            ],
+        }
+    }
+
+    /// Make sure that the site reported in the header of an ephemeris is the
+    /// one given by coordinates. Sites given otherwise, or headers missing
+    /// the coordinates, are not checked.
+    pub(crate) fn check_site(&self, metadata: &EphemerisMetadata) -> Result<(), Error> {
+        let (requested, reported) = match self {
+            ObserverSite::Geodetic {
+                longitude,
+                latitude,
+                altitude,
+                ..
+            } => ([*longitude, *latitude, *altitude], metadata.center_geodetic),
+            ObserverSite::Cylindrical {
+                longitude,
+                distance_from_axis,
+                distance_from_equator,
+                ..
+            } => (
+                [*longitude, *distance_from_axis, *distance_from_equator],
+                metadata.center_cylindric,
+            ),
+            _ => return Ok(()),
+        };
+        let Some(reported) = reported else {
+            return Ok(());
+        };
+
+        // Horizons prints east longitudes between 0 and 360 degrees, rounding
+        // all the coordinates.
+        let longitude_difference = (requested[0] - reported[0]).rem_euclid(360.0);
+        let matches = longitude_difference.min(360.0 - longitude_difference) < SITE_TOLERANCE
+            && (requested[1] - reported[1]).abs() < SITE_TOLERANCE
+            && (requested[2] - reported[2]).abs() < SITE_TOLERANCE;
+        if matches {
+            Ok(())
+        } else {
+            Err(Error::SiteMismatch {
+                requested,
+                reported,
+            })
+        }
+    }
+}
+
+/// How far, in degrees or km, the site reported by Horizons can be from the
+/// requested one.
+const SITE_TOLERANCE: f64 = 1e-3;
+
+impl From<BodyId> for ObserverSite {
+    /// Center of the body.
+    fn from(id: BodyId) -> Self {
+        ObserverSite::BodyCenter(id)
+    }
+}
+
+impl Default for ObserverSite {
+    fn default() -> Self {
+        ObserverSite::SUN
+    }
+}
+
+/// Interval between the entries of an ephemeris, known in Horizons as
+/// `STEP_SIZE`. When not given, Horizons uses 60 minutes.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#stepsize>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepSize {
+    /// Fixed step in minutes.
+    Minutes(u32),
+    /// Fixed step in hours.
+    Hours(u32),
+    /// Fixed step in days.
+    Days(u32),
+    /// Step in calendar months.
+    Months(u32),
+    /// Step in calendar years.
+    Years(u32),
+    /// Divide the time span into given number of equal intervals.
+    Intervals(u32),
+}
+
+impl StepSize {
+    /// Value of the `STEP_SIZE` parameter.
+    fn parameter(&self) -> String {
+        match self {
+            StepSize::Minutes(n) => format!("'{} m'", n),
+            StepSize::Hours(n) => format!("'{} h'", n),
+            StepSize::Days(n) => format!("'{} d'", n),
+            StepSize::Months(n) => format!("'{} MO'", n),
+            StepSize::Years(n) => format!("'{} Y'", n),
+            StepSize::Intervals(n) => format!("'{}'", n),
+        }
+    }
+}
+
+/// Horizon against which rise and set of the target are found, known in
+/// Horizons as `R_T_S_ONLY`.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#obsrv>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RiseSetHorizon {
+    /// True visual horizon, accounting for atmospheric refraction.
+    #[default]
+    TrueVisual,
+    /// Geometric horizon, perpendicular to the local zenith.
+    Geometric,
+    /// Radar horizon, following the elevation cut-off of the query.
+    Radar,
+}
+
+impl RiseSetHorizon {
+    /// Value of the `R_T_S_ONLY` parameter.
+    fn parameter(&self) -> &'static str {
+        match self {
+            RiseSetHorizon::TrueVisual => "TVH",
+            RiseSetHorizon::Geometric => "GEO",
+            RiseSetHorizon::Radar => "RAD",
+        }
+    }
+}
+
+/// Quantities listed in a vector ephemeris, known in Horizons as `VEC_TABLE`.
+/// When not given, Horizons uses [`VectorTable::StateAndRange`].
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#vec_table>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorTable {
+    /// Position only.
+    Position,
+    /// Position and velocity.
+    State,
+    /// Position, velocity, light-time, range and range-rate.
+    #[default]
+    StateAndRange,
+    /// Position, light-time, range and range-rate.
+    PositionAndRange,
+    /// Velocity only.
+    Velocity,
+    /// Light-time, range and range-rate only.
+    Range,
+}
+
+impl VectorTable {
+    /// Code of the table, the value of the `VEC_TABLE` parameter.
+    pub(crate) fn code(&self) -> u8 {
+        match self {
+            VectorTable::Position => 1,
+            VectorTable::State => 2,
+            VectorTable::StateAndRange => 3,
+            VectorTable::PositionAndRange => 4,
+            VectorTable::Velocity => 5,
+            VectorTable::Range => 6,
+        }
+    }
+
+    /// Table with given code, as printed in the `Output format` line of the
+    /// header.
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(VectorTable::Position),
+            2 => Some(VectorTable::State),
+            3 => Some(VectorTable::StateAndRange),
+            4 => Some(VectorTable::PositionAndRange),
+            5 => Some(VectorTable::Velocity),
+            6 => Some(VectorTable::Range),
+            _ => None,
+        }
+    }
+}
+
+/// Aberration corrections applied to vectors, known in Horizons as
+/// `VEC_CORR`.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#vec_corr>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorCorrection {
+    /// Geometric states, without any corrections.
+    #[default]
+    Geometric,
+    /// Astrometric states, corrected for down-leg light-time.
+    Astrometric,
+    /// Apparent states, corrected for down-leg light-time and stellar
+    /// aberration.
+    Apparent,
+}
+
+impl VectorCorrection {
+    /// Value of the `VEC_CORR` parameter.
+    fn parameter(&self) -> &'static str {
+        match self {
+            VectorCorrection::Geometric => "NONE",
+            VectorCorrection::Astrometric => "LT",
+            VectorCorrection::Apparent => "LT+S",
+        }
+    }
+
+    /// Correction named in the `Output type` line of the header, e.g.
+    /// `GEOMETRIC cartesian states`.
+    pub(crate) fn from_output_type(output_type: &str) -> Option<Self> {
+        match output_type.split_whitespace().next()? {
+            "GEOMETRIC" => Some(VectorCorrection::Geometric),
+            "ASTROMETRIC" => Some(VectorCorrection::Astrometric),
+            "APPARENT" => Some(VectorCorrection::Apparent),
+            _ => None,
+        }
+    }
+}
+
+/// Reference plane of the coordinates, known in Horizons as `REF_PLANE`.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#refplane>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferencePlane {
+    /// Ecliptic and mean equinox of the reference epoch.
+    #[default]
+    Ecliptic,
+    /// Equatorial plane of the reference system, e.g. the ICRF.
+    Frame,
+    /// Mean equator and node of date of the center body.
+    BodyEquator,
+}
+
+impl ReferencePlane {
+    /// Value of the `REF_PLANE` parameter.
+    fn parameter(&self) -> &'static str {
+        match self {
+            ReferencePlane::Ecliptic => "ECLIPTIC",
+            ReferencePlane::Frame => "FRAME",
+            ReferencePlane::BodyEquator => "'BODY EQUATOR'",
+        }
+    }
+}
+
+/// Reference system of the coordinates, known in Horizons as `REF_SYSTEM`.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#refsystem>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferenceSystem {
+    /// International Celestial Reference Frame, practically the same as the
+    /// equator and equinox of J2000.0.
+    #[default]
+    Icrf,
+    /// FK4 equator and equinox of B1950.0.
+    B1950,
+}
+
+impl ReferenceSystem {
+    /// Value of the `REF_SYSTEM` parameter.
+    fn parameter(&self) -> &'static str {
+        match self {
+            ReferenceSystem::Icrf => "ICRF",
+            ReferenceSystem::B1950 => "B1950",
+        }
+    }
+}
+
+/// Units of distance and time, known in Horizons as `OUT_UNITS`. Angles are
+/// always in degrees.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#units>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputUnits {
+    /// Kilometers and seconds.
+    #[default]
+    KmS,
+    /// Astronomical units and days.
+    AuD,
+    /// Kilometers and days.
+    KmD,
+}
+
+impl OutputUnits {
+    /// Value of the `OUT_UNITS` parameter, which is also how the units are
+    /// printed in the header.
+    pub(crate) fn parameter(&self) -> &'static str {
+        match self {
+            OutputUnits::KmS => "KM-S",
+            OutputUnits::AuD => "AU-D",
+            OutputUnits::KmD => "KM-D",
+        }
+    }
+
+    /// Units given by their `OUT_UNITS` value.
+    pub(crate) fn from_parameter(parameter: &str) -> Option<Self> {
+        [OutputUnits::KmS, OutputUnits::AuD, OutputUnits::KmD]
+            .into_iter()
+            .find(|units| units.parameter() == parameter)
+    }
+}
+
+/// How many discrete epochs are sent in a single request. Horizons accepts up
+/// to 10,000, but such a list would not fit in an URL.
+const TLIST_CHUNK_SIZE: usize = 100;
+
+/// Quantity listed in an observer ephemeris, known in Horizons as one of the
+/// `QUANTITIES`. When none is given, Horizons lists its default selection.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#output>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObserverQuantity {
+    /// Astrometric right ascension and declination, `1`.
+    AstrometricRaDec,
+    /// Apparent right ascension and declination, `2`.
+    ApparentRaDec,
+    /// Apparent azimuth and elevation, `4`.
+    AzimuthElevation,
+    /// Airmass and visual magnitude extinction, `8`.
+    Airmass,
+    /// Visual magnitude and surface brightness, or total and nuclear
+    /// magnitude of comets, `9`.
+    VisualMagnitude,
+    /// Illuminated fraction of the disk, `10`.
+    IlluminatedFraction,
+    /// Solar elongation, with whether the target leads or trails the Sun,
+    /// `23`.
+    SolarElongation,
+    /// Sun-Target-Observer phase angle, `24`.
+    PhaseAngle,
+    /// Phase angle and its bisector, `43`.
+    PhaseAngleBisector,
+}
+
+impl ObserverQuantity {
+    /// Number of the quantity in `QUANTITIES`.
+    pub(crate) fn code(&self) -> u8 {
+        match self {
+            ObserverQuantity::AstrometricRaDec => 1,
+            ObserverQuantity::ApparentRaDec => 2,
+            ObserverQuantity::AzimuthElevation => 4,
+            ObserverQuantity::Airmass => 8,
+            ObserverQuantity::VisualMagnitude => 9,
+            ObserverQuantity::IlluminatedFraction => 10,
+            ObserverQuantity::SolarElongation => 23,
+            ObserverQuantity::PhaseAngle => 24,
+            ObserverQuantity::PhaseAngleBisector => 43,
+        }
+    }
+}
+
+/// Discrete epoch of an ephemeris entry, see [`EphemerisQuery::at_epochs`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Epoch {
+    /// Calendar date and time.
+    DateTime(DateTime<Utc>),
+    /// Julian date, in the time scale of the query.
+    JulianDate(JulianDate),
+}
+
+impl Epoch {
+    /// Julian date of the epoch in given time scale.
+    fn julian_date(&self, time_scale: TimeScale) -> JulianDate {
+        match self {
+            Epoch::DateTime(time) => JulianDate::from_utc(*time, time_scale),
+            Epoch::JulianDate(jd) => *jd,
+        }
+    }
+}
+
+impl From<DateTime<Utc>> for Epoch {
+    fn from(time: DateTime<Utc>) -> Self {
+        Epoch::DateTime(time)
+    }
+}
+
+impl From<JulianDate> for Epoch {
+    fn from(jd: JulianDate) -> Self {
+        Epoch::JulianDate(jd)
+    }
+}
+
+impl From<f64> for Epoch {
+    fn from(jd: f64) -> Self {
+        Epoch::JulianDate(jd.into())
+    }
+}
+
+/// When should the entries of an ephemeris be computed.
+#[derive(Debug, Clone, PartialEq)]
+enum Times {
+    /// Regular grid between two points in time.
+    Span {
+        start_time: DateTime<Utc>,
+        stop_time: DateTime<Utc>,
+    },
+    /// Arbitrary, discrete epochs.
+    List(Vec<Epoch>),
+}
+
+/// Description of an ephemeris to be computed by Horizons. The same query can
+/// be used to get either vectors or orbital elements.
+///
+/// Example
+/// ```no_run
+/// # use chrono::{Duration, Utc};
+/// # use rhorizons::{BodyId, EphemerisQuery, HorizonsClient, StepSize};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), rhorizons::Error> {
+/// // Position of the Moon relative to the Earth.
+/// let query = EphemerisQuery::new(BodyId::MOON, Utc::now(), Utc::now() + Duration::days(1))
+///     .center(BodyId::EARTH)
+///     .step_size(StepSize::Hours(1));
+///
+/// let vectors = HorizonsClient::default().vectors(&query).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EphemerisQuery {
+    target: BodyId,
+    center: ObserverSite,
+    times: Times,
+    step_size: Option<StepSize>,
+    time_scale: Option<TimeScale>,
+    vector_table: Option<VectorTable>,
+    vector_correction: Option<VectorCorrection>,
+    reference_plane: Option<ReferencePlane>,
+    reference_system: Option<ReferenceSystem>,
+    output_units: Option<OutputUnits>,
+    quantities: Option<Vec<ObserverQuantity>>,
+    visibility: Option<(f64, SolarPresence)>,
+    rise_transit_set: Option<RiseSetHorizon>,
+}
+
+impl EphemerisQuery {
+    /// Ephemeris of `target` body between `start_time` and `stop_time`,
+    /// relative to the Sun's center.
+    pub fn new(target: BodyId, start_time: DateTime<Utc>, stop_time: DateTime<Utc>) -> Self {
+        Self::with_times(
+            target,
+            Times::Span {
+                start_time,
+                stop_time,
+            },
+        )
+    }
+
+    /// Ephemeris of `target` body at given, possibly irregular epochs,
+    /// relative to the Sun's center. Epochs are either `DateTime<Utc>` or
+    /// Julian dates, given as [`JulianDate`] or `f64`.
+    ///
+    /// Long lists are split across several requests, the entries are
+    /// returned in the same order as the epochs.
+    ///
+    /// Example
+    /// ```
+    /// # use chrono::{TimeZone, Utc};
+    /// # use rhorizons::{BodyId, EphemerisQuery};
+    /// let query = EphemerisQuery::at_epochs(
+    ///     BodyId::EARTH,
+    ///     &[
+    ///         Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap(),
+    ///         Utc.with_ymd_and_hms(2022, 6, 23, 3, 12, 0).unwrap(),
+    ///     ],
+    /// );
+    /// let query = EphemerisQuery::at_epochs(BodyId::EARTH, &[2459750.25, 2459753.6]);
+    /// ```
+    pub fn at_epochs<E: Into<Epoch> + Copy>(target: BodyId, epochs: &[E]) -> Self {
+        Self::with_times(
+            target,
+            Times::List(epochs.iter().map(|&epoch| epoch.into()).collect()),
+        )
+    }
+
+    /// Ephemeris of `target` body at given times, with all other settings
+    /// left to their defaults.
+    fn with_times(target: BodyId, times: Times) -> Self {
+        Self {
+            target,
+            center: ObserverSite::default(),
+            times,
+            step_size: None,
+            time_scale: None,
+            vector_table: None,
+            vector_correction: None,
+            reference_plane: None,
+            reference_system: None,
+            output_units: None,
+            quantities: None,
+            visibility: None,
+            rise_transit_set: None,
+        }
+    }
+
+    /// Origin of the coordinates, either an [`ObserverSite`] or the
+    /// [`BodyId`] of the body at the origin. Sites given by coordinates are
+    /// checked against the header of the ephemeris.
+    pub fn center(mut self, center: impl Into<ObserverSite>) -> Self {
+        self.center = center.into();
+        self
+    }
+
+    /// Interval between the entries. Ignored when the ephemeris is computed at
+    /// discrete epochs.
+    pub fn step_size(mut self, step_size: StepSize) -> Self {
+        self.step_size = Some(step_size);
+        self
+    }
+
+    /// Time scale used by Horizons, which is also the scale of Julian dates
+    /// in the query and in the ephemeris. Defaults to TDB for vectors and
+    /// orbital elements, and to UT for observer tables. Observer tables are
+    /// not available in TDB, TT is used instead.
+    pub fn time_scale(mut self, time_scale: TimeScale) -> Self {
+        self.time_scale = Some(time_scale);
+        self
+    }
+
+    /// Quantities listed in vector ephemerides. Ignored for orbital
+    /// elements.
+    pub fn vector_table(mut self, vector_table: VectorTable) -> Self {
+        self.vector_table = Some(vector_table);
+        self
+    }
+
+    /// Aberration corrections applied to vectors. Defaults to geometric
+    /// states, ignored for orbital elements.
+    pub fn vector_correction(mut self, vector_correction: VectorCorrection) -> Self {
+        self.vector_correction = Some(vector_correction);
+        self
+    }
+
+    /// Reference plane of positions, velocities and orbital elements.
+    /// Defaults to the ecliptic.
+    pub fn reference_plane(mut self, reference_plane: ReferencePlane) -> Self {
+        self.reference_plane = Some(reference_plane);
+        self
+    }
+
+    /// Reference system of positions, velocities and orbital elements.
+    /// Defaults to the ICRF.
+    pub fn reference_system(mut self, reference_system: ReferenceSystem) -> Self {
+        self.reference_system = Some(reference_system);
+        self
+    }
+
+    /// Units of distance and time of vectors and orbital elements. Defaults
+    /// to km and s. The units are recorded in the metadata of the ephemeris,
+    /// see [`EphemerisMetadata::output_units`](crate::EphemerisMetadata::output_units).
+    pub fn output_units(mut self, output_units: OutputUnits) -> Self {
+        self.output_units = Some(output_units);
+        self
+    }
+
+    /// Quantities listed in observer ephemerides. Ignored for vectors and
+    /// orbital elements.
+    pub fn quantities(mut self, quantities: &[ObserverQuantity]) -> Self {
+        self.quantities = Some(quantities.to_vec());
+        self
+    }
+
+    /// Keep only the entries of an observer ephemeris where the target is at
+    /// least `min_elevation` degrees above the horizon while the Sun is no
+    /// brighter than `max_solar_presence`, e.g. in nautical twilight or
+    /// darker. Ignored for vectors and orbital elements.
+    ///
+    /// Horizons skips the entries below the elevation and, unless daylight is
+    /// allowed, those in daylight. The rest is checked by the client, see
+    /// [`EphemerisObserverItem::is_visible`](crate::EphemerisObserverItem::is_visible).
+    pub fn visibility(mut self, min_elevation: f64, max_solar_presence: SolarPresence) -> Self {
+        self.visibility = Some((min_elevation, max_solar_presence));
+        self
+    }
+
+    /// List only the entries where the target rises, transits or sets, seen
+    /// from a site on the surface of a body. Azimuth and elevation are
+    /// always listed. Ignored for vectors and orbital elements. The
+    /// [`EphemerisQuery::visibility`] does not apply to the events, except
+    /// the minimum elevation for [`RiseSetHorizon::Radar`].
+    ///
+    /// See also [`HorizonsClient::rise_transit_set`](crate::HorizonsClient::rise_transit_set).
+    pub fn rise_transit_set(mut self, horizon: RiseSetHorizon) -> Self {
+        self.rise_transit_set = Some(horizon);
+        self
+    }
+
+    /// Whether an entry of an observer ephemeris is to be kept, see
+    /// [`EphemerisQuery::visibility`].
+    pub(crate) fn is_visible(&self, item: &EphemerisObserverItem) -> bool {
+        self.visibility
+            .is_none_or(|(min_elevation, max_solar_presence)| {
+                item.is_visible(min_elevation, max_solar_presence)
+            })
+    }
+
+    /// Make sure that the ephemeris was computed for the site of the query,
+    /// see [`ObserverSite::check_site`].
+    pub(crate) fn check_site(&self, metadata: &EphemerisMetadata) -> Result<(), Error> {
+        self.center.check_site(metadata)
+    }
+
+    /// Time scale of the query for given `ephem_type`, see
+    /// [`EphemerisQuery::time_scale`].
+    fn time_scale_for(&self, ephem_type: &str) -> TimeScale {
+        if ephem_type != "OBSERVER" {
+            return self.time_scale.unwrap_or_default();
+        }
+        match self.time_scale {
+            None | Some(TimeScale::Utc) => TimeScale::Utc,
+            Some(TimeScale::Tt) | Some(TimeScale::Tdb) => TimeScale::Tt,
+        }
+    }
+
+    /// Format time for `START_TIME` and `STOP_TIME` parameters, converting it
+    /// to given time scale. Horizons reads the seconds to a millisecond at
+    /// most.
+    fn format_time(time: DateTime<Utc>, time_scale: TimeScale) -> String {
+        let time: NaiveDateTime = time_scale.from_utc(time);
+        let time = time
+            .duration_round(Duration::milliseconds(1))
+            .unwrap_or(time);
+        // https://ssd.jpl.nasa.gov/horizons/manual.html#time
+        time.format("%Y-%b-%d-%H:%M:%S%.f").to_string()
+    }
+
+    /// Parameters of the Horizons API describing this query, `ephem_type`
+    /// being `VECTORS`, `ELEMENTS` or `OBSERVER`. Every item is a separate request.
+    pub(crate) fn requests(&self, ephem_type: &str) -> Vec<Vec<(&'static str, String)>> {
+        let time_scale = self.time_scale_for(ephem_type);
+        let mut common = vec![("COMMAND", self.target.command())];
+        common.extend(self.center.parameters());
+        common.extend([
+            ("EPHEM_TYPE", ephem_type.to_string()),
+            ("TIME_TYPE", time_scale.parameter().to_string()),
+        ]);
+        if ephem_type == "VECTORS" {
+            if let Some(vector_table) = &self.vector_table {
+                common.push(("VEC_TABLE", vector_table.code().to_string()));
+            }
+            if let Some(vector_correction) = &self.vector_correction {
+                common.push(("VEC_CORR", vector_correction.parameter().to_string()));
+            }
+        }
+        // Observer tables are always equatorial, in the units of their own
+        // quantities.
+        let observer = ephem_type == "OBSERVER";
+        if let Some(reference_plane) = self.reference_plane.filter(|_| !observer) {
+            common.push(("REF_PLANE", reference_plane.parameter().to_string()));
+        }
+        if let Some(reference_system) = &self.reference_system {
+            common.push(("REF_SYSTEM", reference_system.parameter().to_string()));
+        }
+        if let Some(output_units) = self.output_units.filter(|_| !observer) {
+            common.push(("OUT_UNITS", output_units.parameter().to_string()));
+        }
+        let mut quantities = self.quantities.clone().unwrap_or_default();
+        let rise_transit_set = self.rise_transit_set.filter(|_| observer);
+        if let Some(horizon) = rise_transit_set {
+            common.push(("R_T_S_ONLY", horizon.parameter().to_string()));
+            // Position of the target at the events.
+            if !quantities.contains(&ObserverQuantity::AzimuthElevation) {
+                quantities.push(ObserverQuantity::AzimuthElevation);
+            }
+        }
+        if observer && !quantities.is_empty() {
+            let codes: Vec<_> = quantities.iter().map(|q| q.code().to_string()).collect();
+            common.push(("QUANTITIES", format!("'{}'", codes.join(","))));
+        }
+        if let Some((min_elevation, max_solar_presence)) = self.visibility.filter(|_| observer) {
+            // Events are listed whether the target is visible or not, only
+            // the radar horizon follows the elevation cut-off.
+            match rise_transit_set {
+                None => {
+                    common.push(("ELEV_CUT", min_elevation.to_string()));
+                    if max_solar_presence < SolarPresence::Daylight {
+                        common.push(("SKIP_DAYLT", "YES".to_string()));
+                    }
+                }
+                Some(RiseSetHorizon::Radar) => {
+                    common.push(("ELEV_CUT", min_elevation.to_string()));
+                }
+                Some(_) => {}
+            }
+        }
+
+        match &self.times {
+            Times::Span {
+                start_time,
+                stop_time,
+            } => {
+                let mut parameters = common;
+                parameters.extend([
+                    ("START_TIME", Self::format_time(*start_time, time_scale)),
+                    ("STOP_TIME", Self::format_time(*stop_time, time_scale)),
+                ]);
+                if let Some(step_size) = &self.step_size {
+                    parameters.push(("STEP_SIZE", step_size.parameter()));
+                }
+                vec![parameters]
+            }
+            Times::List(epochs) => epochs
+                .chunks(TLIST_CHUNK_SIZE)
+                .map(|chunk| {
+                    let mut parameters = common.clone();
+                    parameters.extend([
+                        ("TLIST_TYPE", "JD".to_string()),
+                        (
+                            "TLIST",
+                            chunk
+                                .iter()
+                                .map(|epoch| format!("'{}'", epoch.julian_date(time_scale)))
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                        ),
+                    ]);
+                    parameters
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+
+    use super::*;
+
+    /// Ephemeris of the Earth over 2022, for tests of single parameters.
+    fn earth_query() -> EphemerisQuery {
+        EphemerisQuery::new(
+            BodyId::EARTH,
+            Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+        )
+    }
+
+    /// Whether a parameter with given name is present, whatever its value.
+    fn has_parameter(parameters: &[(&str, String)], name: &str) -> bool {
+        parameters.iter().any(|(n, _)| *n == name)
+    }
+
+    #[test]
+    fn site_parameters() {
+        assert_eq!(
+            vec![("CENTER", "500@399".to_string())],
+            ObserverSite::from(BodyId::EARTH).parameters()
+        );
+        assert_eq!(
+            vec![("CENTER", "500@0".to_string())],
+            ObserverSite::SOLAR_SYSTEM_BARYCENTER.parameters()
+        );
+        assert_eq!(
+            vec![("CENTER", "500@10".to_string())],
+            ObserverSite::default().parameters()
+        );
+        assert_eq!(
+            vec![("CENTER", "568@399".to_string())],
+            ObserverSite::Observatory {
+                code: "568".to_string(),
+                body: BodyId::EARTH,
+            }
+            .parameters()
+        );
+        assert_eq!(
+            vec![
+                ("CENTER", "coord@399".to_string()),
+                ("COORD_TYPE", "GEODETIC".to_string()),
+                ("SITE_COORD", "'243.1104,35.4267,1'".to_string()),
+            ],
+            ObserverSite::Geodetic {
+                body: BodyId::EARTH,
+                longitude: 243.1104,
+                latitude: 35.4267,
+                altitude: 1.0
+            }
+            .parameters()
+        );
+        assert_eq!(
+            vec![
+                ("CENTER", "coord@399".to_string()),
+                ("COORD_TYPE", "CYLINDRICAL".to_string()),
+                ("SITE_COORD", "'243.1104,5200.5,3668.25'".to_string()),
+            ],
+            ObserverSite::Cylindrical {
+                body: BodyId::EARTH,
+                longitude: 243.1104,
+                distance_from_axis: 5200.5,
+                distance_from_equator: 3668.25,
+            }
+            .parameters()
+        );
+    }
+
+    #[test]
+    fn checking_sites() {
+        let goldstone = ObserverSite::Geodetic {
+            body: BodyId::EARTH,
+            longitude: -116.8896,
+            latitude: 35.4267,
+            altitude: 1.0,
+        };
+        let metadata = |geodetic| EphemerisMetadata {
+            center_geodetic: Some(geodetic),
+            ..Default::default()
+        };
+
+        assert!(goldstone
+            .check_site(&metadata([243.110400, 35.4267000, 1.0000000]))
+            .is_ok());
+        assert!(goldstone.check_site(&EphemerisMetadata::default()).is_ok());
+        assert!(matches!(
+            goldstone.check_site(&metadata([0.0, 0.0, -6378.137])),
+            Err(Error::SiteMismatch {
+                reported: [0.0, 0.0, -6378.137],
+                ..
+            })
+        ));
+        // Only coordinates are checked.
+        assert!(ObserverSite::from(BodyId::EARTH)
+            .check_site(&metadata([0.0, 0.0, -6378.137]))
+            .is_ok());
+    }
+
+    #[test]
+    fn query_parameters() {
+        let query = EphemerisQuery::new(
+            BodyId::MOON,
+            Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2022, 6, 20, 18, 0, 0).unwrap(),
+        )
+        .center(BodyId::EARTH);
+
+        assert_eq!(
+            vec![
+                ("COMMAND", "301".to_string()),
+                ("CENTER", "500@399".to_string()),
+                ("EPHEM_TYPE", "VECTORS".to_string()),
+                ("TIME_TYPE", "UT".to_string()),
+                ("START_TIME", "2022-Jun-19-18:00:00".to_string()),
+                ("STOP_TIME", "2022-Jun-20-18:00:00".to_string()),
+            ],
+            query.clone().time_scale(TimeScale::Utc).requests("VECTORS")[0]
+        );
+
+        // Times are converted to the time scale of the query.
+        let parameters = query.clone().time_scale(TimeScale::Tt).requests("VECTORS");
+        assert_eq!(("TIME_TYPE", "TT".to_string()), parameters[0][3]);
+        assert_eq!(
+            ("START_TIME", "2022-Jun-19-18:01:09.184".to_string()),
+            parameters[0][4]
+        );
+
+        // Fractions of a second are rounded to milliseconds.
+        let parameters = query.requests("VECTORS");
+        assert_eq!(("TIME_TYPE", "TDB".to_string()), parameters[0][3]);
+        assert_eq!(
+            ("START_TIME", "2022-Jun-19-18:01:09.184".to_string()),
+            parameters[0][4]
+        );
+
+        // UTC instant of a whole second in TDB is sent as that second.
+        let noon_tdb = NaiveDate::from_ymd_opt(2016, 10, 15)
+            .and_then(|date| date.and_hms_opt(12, 0, 0))
+            .unwrap();
+        let query = EphemerisQuery::new(
+            BodyId::EARTH,
+            TimeScale::Tdb.to_utc(noon_tdb),
+            TimeScale::Tdb.to_utc(noon_tdb) + Duration::hours(1),
+        );
+        assert_eq!(
+            ("START_TIME", "2016-Oct-15-12:00:00".to_string()),
+            query.requests("VECTORS")[0][4]
+        );
+    }
+
+    #[test]
+    fn step_size_parameters() {
+        assert_eq!("'10 m'", StepSize::Minutes(10).parameter());
+        assert_eq!("'1 h'", StepSize::Hours(1).parameter());
+        assert_eq!("'1 d'", StepSize::Days(1).parameter());
+        assert_eq!("'3 MO'", StepSize::Months(3).parameter());
+        assert_eq!("'1 Y'", StepSize::Years(1).parameter());
+        assert_eq!("'100'", StepSize::Intervals(100).parameter());
+
+        let query = earth_query().step_size(StepSize::Days(1));
+
+        for ephem_type in ["VECTORS", "ELEMENTS"] {
+            assert_eq!(
+                Some(&("STEP_SIZE", "'1 d'".to_string())),
+                query.requests(ephem_type)[0].last()
+            );
+        }
+    }
+
+    #[test]
+    fn vector_table_parameters() {
+        let query = earth_query();
+        assert!(!has_parameter(&query.requests("VECTORS")[0], "VEC_TABLE"));
+
+        let query = query.vector_table(VectorTable::PositionAndRange);
+        assert!(query.requests("VECTORS")[0].contains(&("VEC_TABLE", "4".to_string())));
+        assert!(!has_parameter(&query.requests("ELEMENTS")[0], "VEC_TABLE"));
+
+        for code in 1..=6 {
+            assert_eq!(code, VectorTable::from_code(code).unwrap().code());
+        }
+        assert_eq!(None, VectorTable::from_code(7));
+    }
+
+    #[test]
+    fn vector_correction_parameters() {
+        let query = earth_query().vector_correction(VectorCorrection::Apparent);
+
+        assert!(query.requests("VECTORS")[0].contains(&("VEC_CORR", "LT+S".to_string())));
+        assert!(!has_parameter(&query.requests("ELEMENTS")[0], "VEC_CORR"));
+
+        assert_eq!(
+            Some(VectorCorrection::Astrometric),
+            VectorCorrection::from_output_type("ASTROMETRIC cartesian states")
+        );
+        assert_eq!(
+            Some(VectorCorrection::Geometric),
+            VectorCorrection::from_output_type("GEOMETRIC osculating elements")
+        );
+        assert_eq!(None, VectorCorrection::from_output_type(""));
+    }
+
+    #[test]
+    fn reference_frame_parameters() {
+        let query = earth_query()
+            .reference_plane(ReferencePlane::BodyEquator)
+            .reference_system(ReferenceSystem::B1950);
+
+        for ephem_type in ["VECTORS", "ELEMENTS"] {
+            let parameters = &query.requests(ephem_type)[0];
+            assert!(parameters.contains(&("REF_PLANE", "'BODY EQUATOR'".to_string())));
+            assert!(parameters.contains(&("REF_SYSTEM", "B1950".to_string())));
+        }
+        assert_eq!("FRAME", ReferencePlane::Frame.parameter());
+        assert_eq!("ICRF", ReferenceSystem::default().parameter());
+    }
+
+    #[test]
+    fn output_units_parameters() {
+        let query = earth_query().output_units(OutputUnits::AuD);
+
+        for ephem_type in ["VECTORS", "ELEMENTS"] {
+            assert!(query.requests(ephem_type)[0].contains(&("OUT_UNITS", "AU-D".to_string())));
+        }
+        assert_eq!(Some(OutputUnits::KmD), OutputUnits::from_parameter("KM-D"));
+        assert_eq!(None, OutputUnits::from_parameter("KM"));
+    }
+
+    #[test]
+    fn observer_parameters() {
+        let query = earth_query()
+            .quantities(&[
+                ObserverQuantity::AstrometricRaDec,
+                ObserverQuantity::ApparentRaDec,
+            ])
+            .reference_plane(ReferencePlane::Frame)
+            .output_units(OutputUnits::AuD);
+
+        let parameters = &query.requests("OBSERVER")[0];
+        assert!(parameters.contains(&("EPHEM_TYPE", "OBSERVER".to_string())));
+        assert!(parameters.contains(&("TIME_TYPE", "UT".to_string())));
+        assert!(parameters.contains(&("QUANTITIES", "'1,2'".to_string())));
+        assert!(!has_parameter(parameters, "REF_PLANE"));
+        assert!(!has_parameter(parameters, "OUT_UNITS"));
+
+        let parameters = &query.requests("VECTORS")[0];
+        assert!(!has_parameter(parameters, "QUANTITIES"));
+
+        let query = query.visibility(15.0, SolarPresence::NauticalTwilight);
+        let parameters = &query.requests("OBSERVER")[0];
+        assert!(parameters.contains(&("ELEV_CUT", "15".to_string())));
+        assert!(parameters.contains(&("SKIP_DAYLT", "YES".to_string())));
+
+        let query = query.visibility(0.0, SolarPresence::Daylight);
+        let parameters = &query.requests("OBSERVER")[0];
+        assert!(!has_parameter(parameters, "SKIP_DAYLT"));
+
+        let query = query.rise_transit_set(RiseSetHorizon::Geometric);
+        let parameters = &query.requests("OBSERVER")[0];
+        assert!(parameters.contains(&("R_T_S_ONLY", "GEO".to_string())));
+        assert!(parameters.contains(&("QUANTITIES", "'1,2,4'".to_string())));
+    }
+
+    #[test]
+    fn rise_transit_set_visibility() {
+        let query = earth_query().visibility(15.0, SolarPresence::Night);
+
+        let parameters = &query
+            .clone()
+            .rise_transit_set(RiseSetHorizon::TrueVisual)
+            .requests("OBSERVER")[0];
+        assert!(!has_parameter(parameters, "ELEV_CUT"));
+        assert!(!has_parameter(parameters, "SKIP_DAYLT"));
+
+        // The radar horizon is the elevation cut-off.
+        let parameters = &query
+            .rise_transit_set(RiseSetHorizon::Radar)
+            .requests("OBSERVER")[0];
+        assert!(parameters.contains(&("R_T_S_ONLY", "RAD".to_string())));
+        assert!(parameters.contains(&("ELEV_CUT", "15".to_string())));
+        assert!(!has_parameter(parameters, "SKIP_DAYLT"));
+    }
+
+    #[test]
+    fn observer_time_scales() {
+        let time_type = |query: EphemerisQuery, ephem_type| {
+            query.requests(ephem_type)[0]
+                .iter()
+                .find(|(name, _)| *name == "TIME_TYPE")
+                .map(|(_, value)| value.clone())
+        };
+
+        // Observer tables are not available in TDB.
+        assert_eq!(Some("UT".to_string()), time_type(earth_query(), "OBSERVER"));
+        assert_eq!(Some("TDB".to_string()), time_type(earth_query(), "VECTORS"));
+        let query = earth_query().time_scale(TimeScale::Tdb);
+        assert_eq!(Some("TT".to_string()), time_type(query.clone(), "OBSERVER"));
+        assert_eq!(Some("TDB".to_string()), time_type(query, "ELEMENTS"));
+        let query = earth_query().time_scale(TimeScale::Tt);
+        assert_eq!(Some("TT".to_string()), time_type(query, "OBSERVER"));
+
+        // Times are sent in the scale of the table.
+        let parameters = &earth_query().requests("OBSERVER")[0];
+        assert!(parameters.contains(&("START_TIME", "2022-Jan-01-00:00:00".to_string())));
+    }
+
+    #[test]
+    fn epoch_list_parameters() {
+        let epoch = Epoch::from(Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap());
+        assert_eq!(
+            JulianDate::new(2459750, 0.25),
+            epoch.julian_date(TimeScale::Utc)
+        );
+        assert_eq!(
+            "2459750.250800741",
+            epoch.julian_date(TimeScale::Tt).to_string()
+        );
+        assert_eq!(
+            JulianDate::new(2459750, 0.25),
+            Epoch::from(2459750.25).julian_date(TimeScale::Tt)
+        );
+
+        let query = EphemerisQuery::at_epochs(
+            BodyId::EARTH,
+            &[
+                Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2022, 6, 20, 6, 0, 0).unwrap(),
+            ],
+        )
+        .time_scale(TimeScale::Utc);
+
+        assert_eq!(
+            vec![vec![
+                ("COMMAND", "399".to_string()),
+                ("CENTER", "500@10".to_string()),
+                ("EPHEM_TYPE", "ELEMENTS".to_string()),
+                ("TIME_TYPE", "UT".to_string()),
+                ("TLIST_TYPE", "JD".to_string()),
+                (
+                    "TLIST",
+                    "'2459750.250000000' '2459750.750000000'".to_string()
+                ),
+            ]],
+            query.requests("ELEMENTS")
+        );
+    }
+
+    #[test]
+    fn splitting_long_epoch_lists() {
+        let epochs: Vec<f64> = (0..250).map(|n| 2459750.0 + n as f64).collect();
+        let requests = EphemerisQuery::at_epochs(BodyId::EARTH, &epochs).requests("VECTORS");
+
+        assert_eq!(3, requests.len());
+        let tlists: Vec<_> = requests
+            .iter()
+            .map(|parameters| &parameters.last().unwrap().1)
+            .collect();
+        assert!(tlists[0].starts_with("'2459750.000000000' '2459751.000000000'"));
+        assert!(tlists[1].starts_with("'2459850.000000000'"));
+        assert!(tlists[2].ends_with("'2459999.000000000'"));
+        assert_eq!(50, tlists[2].split(' ').count());
+
+        assert!(EphemerisQuery::at_epochs::<f64>(BodyId::EARTH, &[])
+            .requests("VECTORS")
+            .is_empty());
+    }
+}
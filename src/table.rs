@@ -0,0 +1,713 @@
+use std::marker::PhantomData;
+
+use chrono::NaiveDateTime;
+
+use crate::{
+    ephemeris::{parse_calendar_time, parse_date_time, EntryTime},
+    error::{Error, ParseError},
+    metadata::EphemerisMetadata,
+    time::{JulianDate, TimeScale},
+    utilities::{take_expecting, take_or_empty},
+};
+
+/// Labels of the quantities in a fixed-width table, one list per line of an
+/// entry, e.g. `[["X", "Y", "Z"], ["VX", "VY", "VZ"]]`.
+pub(crate) type Layout = Vec<Vec<String>>;
+
+/// Labels of fields, as they appear in the entries of fixed-width tables.
+/// Used to report which one is missing.
+const FIELD_LABELS: &[&str] = &[
+    " X =", " Y =", " Z =", " VX=", " VY=", " VZ=", " LT=", " RG=", " RR=", " EC=", " QR=", " IN=",
+    " OM=", " W =", " Tp=", " N =", " MA=", " TA=", " A =", " AD=", " PR=",
+];
+
+/// Item built from an entry of a table.
+pub(crate) trait FromTableEntry: Sized {
+    /// What the header has to contain when there is no default layout,
+    /// reported when it is missing.
+    const HEADER: &'static str = "labels";
+
+    /// Layout assumed when the header does not list the labels.
+    fn default_layout() -> Option<Layout>;
+
+    /// Layout implied by the value of the `Output format` header line, for
+    /// items which depend on it.
+    fn layout_for_format(_format: &str) -> Result<Option<Layout>, ParseError> {
+        Ok(None)
+    }
+
+    fn from_entry(entry: &TableEntry) -> Result<Self, ParseError>;
+
+    /// Item built from a row of an observer table, for items which can be
+    /// read from one.
+    fn from_observer_row(_row: &ObserverRow) -> Result<Self, ParseError> {
+        Err(ParseError::Expected(Self::HEADER))
+    }
+}
+
+/// Single entry of a table: its time and values of the quantities, in the
+/// order of their labels.
+pub(crate) struct TableEntry<'a> {
+    pub(crate) time: EntryTime,
+    pub(crate) labels: &'a [String],
+    pub(crate) values: &'a [f64],
+}
+
+impl TableEntry<'_> {
+    /// Value of the quantity, `None` if the table doesn't list it.
+    pub(crate) fn get(&self, label: &str) -> Option<f64> {
+        self.labels
+            .iter()
+            .position(|l| l == label)
+            .map(|index| self.values[index])
+    }
+
+    pub(crate) fn require(&self, label: &'static str) -> Result<f64, ParseError> {
+        self.get(label).ok_or(ParseError::Expected(label))
+    }
+
+    /// Values of three quantities forming a vector, e.g. `X`, `Y` and `Z`,
+    /// `None` if the table doesn't list the first one.
+    pub(crate) fn vector3(
+        &self,
+        labels: [&'static str; 3],
+    ) -> Result<Option<[f64; 3]>, ParseError> {
+        if self.get(labels[0]).is_none() {
+            return Ok(None);
+        }
+        Ok(Some([
+            self.require(labels[0])?,
+            self.require(labels[1])?,
+            self.require(labels[2])?,
+        ]))
+    }
+}
+
+enum TableParserState {
+    WaitingForSoe,
+    WaitingForDate,
+    /// Entry being read, `row` being the index of its next line in the
+    /// layout.
+    Entry {
+        time: EntryTime,
+        values: Vec<f64>,
+        row: usize,
+    },
+    End,
+}
+
+/// Reader of Horizons tables, either fixed-width or CSV, driven by the labels
+/// listed in the header. Entries are turned into `Item`s.
+pub(crate) struct TableParser<'a, Input: Iterator<Item = &'a str>, Item> {
+    state: TableParserState,
+    input: Input,
+    line_number: usize,
+    metadata: EphemerisMetadata,
+
+    /// Labels listed in the header, collected while the previous lines are
+    /// labels as well.
+    header_layout: Option<Layout>,
+    collecting_labels: bool,
+    /// Layout implied by the `Output format` header line.
+    format_layout: Option<Layout>,
+    /// Columns of a CSV table, read from the header.
+    csv: Option<CsvColumns>,
+    /// Columns of an observer table, read from the header.
+    observer_columns: Option<ObserverColumns>,
+
+    /// Layout of the entries, decided once the header is over.
+    layout: Layout,
+    /// Labels of all the values of an entry.
+    labels: Vec<String>,
+
+    item: PhantomData<Item>,
+}
+
+impl<'a, Input: Iterator<Item = &'a str>, Item: FromTableEntry> TableParser<'a, Input, Item> {
+    pub fn parse(input: Input) -> Self {
+        Self {
+            state: TableParserState::WaitingForSoe,
+            input,
+            line_number: 0,
+            metadata: EphemerisMetadata::default(),
+            header_layout: None,
+            collecting_labels: false,
+            format_layout: None,
+            csv: None,
+            observer_columns: None,
+            layout: Layout::new(),
+            labels: Vec::new(),
+            item: PhantomData,
+        }
+    }
+
+    /// Description of the ephemeris, complete once the entries are reached.
+    pub fn into_metadata(self) -> EphemerisMetadata {
+        self.metadata
+    }
+
+    /// Take note of a line of the header.
+    fn read_header_line(&mut self, line: &str) -> Result<(), ParseError> {
+        if line.starts_with('*') {
+            self.collecting_labels = false;
+        } else if let Some(columns) = CsvColumns::parse(line)? {
+            self.csv = Some(columns);
+        } else if let Some(columns) = ObserverColumns::parse(line) {
+            self.observer_columns = Some(columns);
+        } else if line.trim_end().starts_with("JD") && !line.trim_end().contains(' ') {
+            // E.g. `JDTDB`, followed by lines of labels.
+            self.header_layout = Some(Layout::new());
+            self.collecting_labels = true;
+        } else if self.collecting_labels {
+            if let Some(layout) = &mut self.header_layout {
+                layout.push(line.split_whitespace().map(String::from).collect());
+            }
+        } else if let Some(format) = line.strip_prefix("Output format") {
+            self.format_layout = Item::layout_for_format(format)?;
+        }
+
+        self.metadata.read_header_line(line);
+        Ok(())
+    }
+
+    /// Decide the layout of the entries, preferring the labels listed in the
+    /// header.
+    fn finish_header(&mut self) -> Result<(), ParseError> {
+        if self.observer_columns.is_some() {
+            // Rows are split by the columns themselves.
+            return Ok(());
+        }
+        if let Some(columns) = &self.csv {
+            self.labels = columns.names.iter().skip(2).cloned().collect();
+        } else {
+            self.layout = self
+                .header_layout
+                .take()
+                .or_else(|| self.format_layout.take())
+                .or_else(Item::default_layout)
+                .ok_or(ParseError::Expected(Item::HEADER))?;
+            self.labels = self.layout.concat();
+        }
+        Ok(())
+    }
+
+    /// Feed single line to the state machine, returning an item once it is
+    /// complete.
+    fn step(&mut self, line: &str) -> Result<Option<Item>, ParseError> {
+        // The state is put back unless parsing of the line fails.
+        match std::mem::replace(&mut self.state, TableParserState::End) {
+            TableParserState::WaitingForSoe => {
+                if line.trim() == "$$SOE" {
+                    self.finish_header()?;
+                    self.state = TableParserState::WaitingForDate;
+                } else {
+                    self.read_header_line(line)?;
+                    self.state = TableParserState::WaitingForSoe;
+                }
+            }
+            TableParserState::WaitingForDate => {
+                if line.trim() == "$$EOE" {
+                    self.state = TableParserState::End;
+                } else if let Some(columns) = &self.observer_columns {
+                    let item = Item::from_observer_row(&columns.row(line)?)?;
+                    self.state = TableParserState::WaitingForDate;
+                    return Ok(Some(item));
+                } else if let Some(columns) = &self.csv {
+                    // Whole entry is on a single line.
+                    let (time, values) = columns.row(line)?;
+                    let item = self.item(time, &values)?;
+                    self.state = TableParserState::WaitingForDate;
+                    return Ok(Some(item));
+                } else {
+                    let time = parse_date_time(line)?;
+
+                    if self.layout.is_empty() {
+                        self.state = TableParserState::WaitingForDate;
+                        return self.item(time, &[]).map(Some);
+                    }
+                    self.state = TableParserState::Entry {
+                        time,
+                        values: Vec::with_capacity(self.labels.len()),
+                        row: 0,
+                    };
+                }
+            }
+            TableParserState::Entry {
+                time,
+                mut values,
+                row,
+            } => {
+                let mut rest = line;
+                for label in &self.layout[row] {
+                    let (value, line) = take_field(rest, label)?;
+                    values.push(value);
+                    rest = line;
+                }
+
+                if row + 1 == self.layout.len() {
+                    let item = self.item(time, &values)?;
+                    self.state = TableParserState::WaitingForDate;
+                    return Ok(Some(item));
+                }
+                self.state = TableParserState::Entry {
+                    time,
+                    values,
+                    row: row + 1,
+                };
+            }
+            TableParserState::End => {}
+        }
+        Ok(None)
+    }
+
+    fn item(&self, time: EntryTime, values: &[f64]) -> Result<Item, ParseError> {
+        Item::from_entry(&TableEntry {
+            time,
+            labels: &self.labels,
+            values,
+        })
+    }
+
+    /// Whether the input can end in the current state.
+    fn can_end(&self) -> bool {
+        matches!(
+            self.state,
+            TableParserState::WaitingForSoe
+                | TableParserState::WaitingForDate
+                | TableParserState::End
+        )
+    }
+}
+
+impl<'a, Input: Iterator<Item = &'a str>, Item: FromTableEntry> Iterator
+    for TableParser<'a, Input, Item>
+{
+    type Item = Result<Item, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if matches!(self.state, TableParserState::End) {
+                // Should we drain input iterator?
+                return None;
+            }
+
+            let Some(line) = self.input.next() else {
+                // Input iterator is drained. Nothing to do, unless we are in
+                // the middle of an entry.
+                if self.can_end() {
+                    return None;
+                }
+                self.state = TableParserState::End;
+                return Some(Err(unexpected_end(self.line_number)));
+            };
+            self.line_number += 1;
+
+            match self.step(line) {
+                Ok(Some(item)) => return Some(Ok(item)),
+                Ok(None) => {}
+                Err(reason) => {
+                    self.state = TableParserState::End;
+                    return Some(Err(Error::Parse {
+                        line: self.line_number,
+                        text: line.to_owned(),
+                        reason,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+fn unexpected_end(line: usize) -> Error {
+    Error::Parse {
+        line,
+        text: String::new(),
+        reason: ParseError::UnexpectedEnd,
+    }
+}
+
+/// Take labeled, 22 characters wide numeric field, e.g. ` X = 1.870010427985840E+02`,
+/// returning its value and the rest of the line. Labels are padded to two
+/// characters.
+fn take_field<'a>(line: &'a str, label: &str) -> Result<(f64, &'a str), ParseError> {
+    let label = format!(" {:<2}=", label);
+    let line = take_expecting(line, &label).map_err(|_| {
+        ParseError::Expected(
+            FIELD_LABELS
+                .iter()
+                .find(|known| **known == label)
+                .copied()
+                .unwrap_or("label"),
+        )
+    })?;
+    let (value, line) = take_or_empty(line, 22);
+    let value = value.trim().parse().map_err(ParseError::InvalidNumber)?;
+    Ok((value, line))
+}
+
+/// Columns of a table in CSV format, named in its header, e.g.
+/// `JDTDB, Calendar Date (TDB), X, Y, Z,`.
+#[derive(Debug, Clone, PartialEq)]
+struct CsvColumns {
+    names: Vec<String>,
+    /// Time scale of the calendar date, which is not repeated in the rows.
+    time_scale: TimeScale,
+}
+
+impl CsvColumns {
+    /// Columns named in the line, if it is the header of a CSV table. The
+    /// header has to name at least the Julian date and the calendar date.
+    fn parse(line: &str) -> Result<Option<Self>, ParseError> {
+        if !line.trim_start().starts_with("JD") || !line.contains(',') {
+            return Ok(None);
+        }
+
+        let names: Vec<_> = line
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if names.len() < 2 {
+            return Err(ParseError::Expected("Calendar Date"));
+        }
+        let time_scale = names
+            .get(1)
+            .and_then(|name| name.strip_prefix("Calendar Date ("))
+            .and_then(|name| name.strip_suffix(')'))
+            .and_then(TimeScale::from_suffix)
+            .unwrap_or(TimeScale::Tdb);
+
+        Ok(Some(Self { names, time_scale }))
+    }
+
+    /// Split a row into the time of the entry and values of the remaining
+    /// columns.
+    fn row(&self, line: &str) -> Result<(EntryTime, Vec<f64>), ParseError> {
+        let mut fields = line.split(',').map(str::trim);
+
+        let julian_date = fields
+            .next()
+            .unwrap_or_default()
+            .parse::<JulianDate>()
+            .map_err(ParseError::InvalidNumber)?;
+        let date = fields.next().ok_or(ParseError::Expected(","))?;
+        let time = EntryTime {
+            julian_date,
+            time: parse_calendar_time(date, self.time_scale)?,
+        };
+
+        let values = fields
+            .take(self.names.len() - 2)
+            .map(|value| value.parse().map_err(ParseError::InvalidNumber))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.len() + 2 < self.names.len() {
+            return Err(ParseError::Expected(","));
+        }
+
+        Ok((time, values))
+    }
+}
+
+/// Number of flags following the date of a row of an observer table.
+const MAX_FLAGS: usize = 3;
+
+/// Column of an observer table, named in its header.
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    label: String,
+    /// Where the label starts and ends in the header of a fixed-width table.
+    start: usize,
+    end: usize,
+}
+
+/// Columns of an observer table, read from the line naming them, e.g.
+/// ` Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC  R.A.__(a-apparent)__DEC`.
+#[derive(Debug, Clone, PartialEq)]
+struct ObserverColumns {
+    /// Time scale of the dates, which is not repeated in the rows.
+    time_scale: TimeScale,
+    /// Where the date ends in the rows of a fixed-width table.
+    date_end: usize,
+    /// Number of single-character flag columns following the date in a CSV
+    /// table.
+    csv_flags: Option<usize>,
+    columns: Vec<Column>,
+}
+
+impl ObserverColumns {
+    /// Columns named in the line, if it is the header of an observer table.
+    fn parse(line: &str) -> Option<Self> {
+        let date = line.trim_start();
+        if !date.starts_with("Date_") {
+            return None;
+        }
+        // Only calendar dates are supported, e.g. `Date__(UT)__HR:MN:SC.fff`.
+        let time_scale = date
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split_once(')'))
+            .and_then(|(suffix, _)| TimeScale::from_suffix(suffix))?;
+
+        if line.contains(',') {
+            let mut labels = line
+                .trim_end()
+                .trim_end_matches(',')
+                .split(',')
+                .skip(1)
+                .map(str::trim)
+                .peekable();
+            let mut flags = 0;
+            while labels.next_if(|label| label.is_empty()).is_some() {
+                flags += 1;
+            }
+            let columns = labels
+                .map(|label| Column {
+                    label: label.to_string(),
+                    start: 0,
+                    end: 0,
+                })
+                .collect();
+            return Some(Self {
+                time_scale,
+                date_end: 0,
+                csv_flags: Some(flags),
+                columns,
+            });
+        }
+
+        // Labels are runs of anything but spaces.
+        let mut labels = Vec::new();
+        let mut start = None;
+        for (index, c) in line.char_indices().chain([(line.len(), ' ')]) {
+            match (c == ' ', start) {
+                (false, None) => start = Some(index),
+                (true, Some(label_start)) => {
+                    labels.push(Column {
+                        label: line[label_start..index].to_string(),
+                        start: label_start,
+                        end: index,
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        let date_end = labels.remove(0).end;
+
+        Some(Self {
+            time_scale,
+            date_end,
+            csv_flags: None,
+            columns: labels,
+        })
+    }
+
+    /// Split a row into its time, flags and the text of the remaining
+    /// columns.
+    fn row<'a>(&'a self, line: &'a str) -> Result<ObserverRow<'a>, ParseError> {
+        match self.csv_flags {
+            Some(flags) => {
+                let mut fields = line.split(',').map(str::trim);
+                let date = fields.next().unwrap_or_default();
+                let flags: String = fields
+                    .by_ref()
+                    .take(flags)
+                    .map(|flag| flag.chars().next().unwrap_or(' '))
+                    .collect();
+
+                let fields: Vec<_> = self
+                    .columns
+                    .iter()
+                    .map(|column| column.label.as_str())
+                    .zip(fields)
+                    .collect();
+                if fields.len() < self.columns.len() {
+                    return Err(ParseError::Expected(","));
+                }
+
+                Ok(ObserverRow {
+                    time: parse_time(date, self.time_scale)?,
+                    flags,
+                    fields,
+                })
+            }
+            None => {
+                // Flags are between the date and the first column, at most
+                // one each for the Sun, the Moon and events of the target.
+                // The first value may be wider than its label, e.g. a
+                // negative magnitude under `APmag`.
+                let first_start = self
+                    .columns
+                    .first()
+                    .map_or(line.len(), |c| c.start)
+                    .min(self.date_end + 1 + MAX_FLAGS);
+                let flags = slice(line, self.date_end + 1, first_start);
+
+                // Values are aligned to the end of their labels, but may be
+                // wider.
+                let mut fields = Vec::with_capacity(self.columns.len());
+                let mut start = first_start;
+                for (index, column) in self.columns.iter().enumerate() {
+                    let end = if index + 1 == self.columns.len() {
+                        line.len()
+                    } else {
+                        column.end
+                    };
+                    fields.push((column.label.as_str(), slice(line, start, end).trim()));
+                    start = end;
+                }
+
+                Ok(ObserverRow {
+                    time: parse_time(slice(line, 0, self.date_end).trim(), self.time_scale)?,
+                    flags: flags.to_string(),
+                    fields,
+                })
+            }
+        }
+    }
+}
+
+/// Single row of an observer table.
+pub(crate) struct ObserverRow<'a> {
+    pub(crate) time: EntryTime,
+    /// Flags following the date, e.g. `*m`, one character each.
+    pub(crate) flags: String,
+    /// Text of the columns by their labels.
+    pub(crate) fields: Vec<(&'a str, &'a str)>,
+}
+
+/// Part of the line between byte offsets, cut short where the line ends.
+fn slice(line: &str, start: usize, end: usize) -> &str {
+    line.get(start.min(line.len())..end.min(line.len()))
+        .unwrap_or_default()
+}
+
+/// Parse the date of a row, e.g. `2022-Aug-13 00:00` or
+/// `2022-Aug-13 00:00:00.000`, converting it to UTC. The Julian date is in
+/// the time scale of the table.
+fn parse_time(text: &str, time_scale: TimeScale) -> Result<EntryTime, ParseError> {
+    let format = if text.matches(':').count() == 1 {
+        "%Y-%b-%d %H:%M"
+    } else {
+        "%Y-%b-%d %H:%M:%S%.f"
+    };
+    let time = NaiveDateTime::parse_from_str(text, format).map_err(ParseError::InvalidDateTime)?;
+    Ok(EntryTime {
+        julian_date: time.into(),
+        time: time_scale.to_utc(time),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Values by label, whatever the table lists.
+    struct Values(Vec<(String, f64)>);
+
+    impl FromTableEntry for Values {
+        fn default_layout() -> Option<Layout> {
+            None
+        }
+
+        fn from_entry(entry: &TableEntry) -> Result<Self, ParseError> {
+            Ok(Values(
+                entry
+                    .labels
+                    .iter()
+                    .cloned()
+                    .zip(entry.values.iter().copied())
+                    .collect(),
+            ))
+        }
+    }
+
+    #[test]
+    fn reading_layout_from_header() {
+        let data = "*******************************************************************************
+JDTDB
+   X     Y     Z
+   LT    RG    RR
+*******************************************************************************
+$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 Y = 2.484687803242536E+03 Z =-5.861602653492581E+03
+ LT= 2.124544811851363E-02 RG= 6.369225112760676E+03 RR= 1.673381117437352E-18
+$$EOE";
+        let mut parser = TableParser::<_, Values>::parse(data.lines());
+        let entries = (&mut parser).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(1, entries.len());
+        let labels: Vec<_> = entries[0]
+            .0
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .collect();
+        assert_eq!(vec!["X", "Y", "Z", "LT", "RG", "RR"], labels);
+        assert_eq!(("RG".to_string(), 6.369225112760676E+03), entries[0].0[4]);
+        assert_eq!(
+            vec![vec!["X", "Y", "Z"], vec!["LT", "RG", "RR"]],
+            parser.layout
+        );
+    }
+
+    #[test]
+    fn splitting_observer_rows() {
+        let columns =
+            ObserverColumns::parse(" Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC").unwrap();
+        let row = columns
+            .row(" 2022-Aug-13 18:00  m  03 28 22.74 +18 16 57.2")
+            .unwrap();
+        assert_eq!(" m ", row.flags);
+        assert_eq!(
+            vec![("R.A._____(ICRF)_____DEC", "03 28 22.74 +18 16 57.2")],
+            row.fields
+        );
+    }
+
+    #[test]
+    fn reading_unknown_labels() {
+        let data = "JDTDB
+   X     FOO
+$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 FOO= 2.484687803242536E+03
+2459805.372175926 = A.D. 2022-Aug-13 20:55:56.0000 TDB
+ X = 1.870010427985840E+02 BAR= 2.484687803242536E+03";
+        let entries: Vec<_> = TableParser::<_, Values>::parse(data.lines()).collect();
+        assert!(matches!(&entries[0], Ok(Values(values)) if values[1].0 == "FOO"));
+        assert!(matches!(
+            entries[1],
+            Err(Error::Parse {
+                line: 7,
+                reason: ParseError::Expected("label"),
+                ..
+            })
+        ));
+
+        // Header without the calendar date.
+        let data = "JDTDB,
+$$SOE
+2459805.5, A.D. 2022-Aug-13 19:55:56.0000,
+$$EOE";
+        let entries: Vec<_> = TableParser::<_, Values>::parse(data.lines()).collect();
+        assert!(matches!(
+            entries[..],
+            [Err(Error::Parse {
+                line: 1,
+                reason: ParseError::Expected("Calendar Date"),
+                ..
+            })]
+        ));
+
+        // Nothing to go by.
+        let entries: Vec<_> = TableParser::<_, Values>::parse("$$SOE\n$$EOE".lines()).collect();
+        assert!(matches!(
+            entries[..],
+            [Err(Error::Parse {
+                line: 1,
+                reason: ParseError::Expected("labels"),
+                ..
+            })]
+        ));
+    }
+}
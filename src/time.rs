@@ -0,0 +1,423 @@
+use std::{fmt, num::ParseFloatError, str::FromStr};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+/// Time scale in which Horizons reads and prints the times, known in Horizons
+/// as `TIME_TYPE`.
+///
+/// Times passed to and returned from this crate are always `DateTime<Utc>`,
+/// the time scale only selects what Horizons works with and how the Julian
+/// dates are expressed. The conversion is done with the built-in table of
+/// leap seconds.
+///
+/// <https://ssd.jpl.nasa.gov/horizons/manual.html#time>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeScale {
+    /// Coordinated Universal Time.
+    Utc,
+    /// Terrestrial Time.
+    Tt,
+    /// Barycentric Dynamical Time, the default of Horizons for vectors and
+    /// orbital elements.
+    #[default]
+    Tdb,
+}
+
+/// Difference between TT and TAI in seconds.
+const TT_MINUS_TAI: f64 = 32.184;
+
+/// Dates (always the first day of a month) since which given number of
+/// seconds is the difference between TAI and UTC.
+///
+/// <https://hpiers.obspm.fr/iers/bul/bulc/Leap_Second.dat>
+const LEAP_SECONDS: &[(i32, u32, i64)] = &[
+    (1972, 1, 10),
+    (1972, 7, 11),
+    (1973, 1, 12),
+    (1974, 1, 13),
+    (1975, 1, 14),
+    (1976, 1, 15),
+    (1977, 1, 16),
+    (1978, 1, 17),
+    (1979, 1, 18),
+    (1980, 1, 19),
+    (1981, 7, 20),
+    (1982, 7, 21),
+    (1983, 7, 22),
+    (1985, 7, 23),
+    (1988, 1, 24),
+    (1990, 1, 25),
+    (1991, 1, 26),
+    (1992, 7, 27),
+    (1993, 7, 28),
+    (1994, 7, 29),
+    (1996, 1, 30),
+    (1997, 7, 31),
+    (1999, 1, 32),
+    (2006, 1, 33),
+    (2009, 1, 34),
+    (2012, 7, 35),
+    (2015, 7, 36),
+    (2017, 1, 37),
+];
+
+/// TAI - UTC in seconds at given UTC time. Before 1972, when UTC was not yet
+/// synchronized with TAI in whole seconds, the initial 10 seconds are used.
+fn tai_minus_utc(utc: NaiveDateTime) -> i64 {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(year, month, _)| {
+            NaiveDate::from_ymd_opt(*year, *month, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .is_some_and(|since| utc >= since)
+        })
+        .map_or(LEAP_SECONDS[0].2, |(_, _, seconds)| *seconds)
+}
+
+/// TDB - TT in seconds. The difference is periodic and never exceeds 2 ms.
+fn tdb_minus_tt(time: NaiveDateTime) -> f64 {
+    let days_since_j2000 = (time - j2000()).num_milliseconds() as f64 / 86_400_000.0;
+    let g = (357.53 + 0.98560028 * days_since_j2000).to_radians();
+    0.001657 * g.sin() + 0.000014 * (2.0 * g).sin()
+}
+
+fn j2000() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .expect("valid date")
+}
+
+fn seconds(seconds: f64) -> Duration {
+    Duration::nanoseconds((seconds * 1e9).round() as i64)
+}
+
+impl TimeScale {
+    /// Value of the `TIME_TYPE` parameter.
+    pub(crate) fn parameter(&self) -> &'static str {
+        match self {
+            TimeScale::Utc => "UT",
+            TimeScale::Tt => "TT",
+            TimeScale::Tdb => "TDB",
+        }
+    }
+
+    /// Recognize the suffix which Horizons puts after the dates, e.g.
+    /// `A.D. 2022-Jun-19 18:00:00.0000 TDB`.
+    pub(crate) fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "UT" | "UTC" => Some(TimeScale::Utc),
+            "TT" => Some(TimeScale::Tt),
+            "TDB" => Some(TimeScale::Tdb),
+            _ => None,
+        }
+    }
+
+    /// Reading of a clock running in this time scale at given UTC instant.
+    pub fn from_utc(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        let utc = time.naive_utc();
+        match self {
+            TimeScale::Utc => utc,
+            TimeScale::Tt => utc + seconds(tai_minus_utc(utc) as f64 + TT_MINUS_TAI),
+            TimeScale::Tdb => {
+                let tt = TimeScale::Tt.from_utc(time);
+                tt + seconds(tdb_minus_tt(tt))
+            }
+        }
+    }
+
+    /// UTC instant at which a clock running in this time scale shows given
+    /// time.
+    pub fn to_utc(&self, time: NaiveDateTime) -> DateTime<Utc> {
+        match self {
+            TimeScale::Utc => time.and_utc(),
+            TimeScale::Tt => {
+                let tai = time - seconds(TT_MINUS_TAI);
+                // Leap seconds are tabulated in UTC, so look them up once more
+                // when the first guess lands on the other side of one.
+                let guess = tai - Duration::seconds(tai_minus_utc(tai));
+                (tai - Duration::seconds(tai_minus_utc(guess))).and_utc()
+            }
+            TimeScale::Tdb => TimeScale::Tt.to_utc(time - seconds(tdb_minus_tt(time))),
+        }
+    }
+}
+
+/// Julian Day Number of the Unix epoch, 1970-01-01 00:00.
+const UNIX_EPOCH_JD: i64 = 2440587;
+
+/// Days from January 1, 1 CE to the Unix epoch, counting the former as day 1.
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719163;
+
+const NANOS_PER_DAY: f64 = 86400e9;
+
+/// Julian date, the number of days since noon of January 1, 4713 BC, as
+/// printed by Horizons in the first column of every entry, e.g.
+/// `2459750.250000000`.
+///
+/// Whole days and the fraction of a day are kept separately, because a
+/// single `f64` holding a present day Julian date is only precise to tens of
+/// microseconds. Julian date itself is not bound to any time scale, in
+/// ephemerides it is in the scale of the query, see [`TimeScale`].
+///
+/// Example
+/// ```
+/// # use chrono::NaiveDate;
+/// # use rhorizons::{JulianDate, TimeScale};
+/// let jd: JulianDate = "2459750.250000000".parse().unwrap();
+/// let time = NaiveDate::from_ymd_opt(2022, 6, 19)
+///     .unwrap()
+///     .and_hms_opt(18, 0, 0)
+///     .unwrap();
+///
+/// assert_eq!(Some(time), jd.to_naive());
+/// assert_eq!(jd, JulianDate::from(time));
+/// assert_eq!("2459750.250000000", jd.to_string());
+///
+/// // Instant at which TDB clocks show that date.
+/// let utc = jd.to_utc(TimeScale::Tdb);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct JulianDate {
+    day: i64,
+    /// Always in `[0, 1)`.
+    fraction: f64,
+}
+
+impl JulianDate {
+    /// Julian date made of whole days and a fraction of a day. The fraction
+    /// doesn't have to be smaller than one, it is normalized.
+    pub fn new(day: i64, fraction: f64) -> Self {
+        let whole = fraction.floor();
+        Self {
+            day: day + whole as i64,
+            fraction: fraction - whole,
+        }
+    }
+
+    /// Whole days.
+    pub fn day(&self) -> i64 {
+        self.day
+    }
+
+    /// Fraction of the day, in `[0, 1)`. Note that Julian days start at noon.
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    /// Julian date as a single number, losing some precision.
+    pub fn to_f64(&self) -> f64 {
+        self.day as f64 + self.fraction
+    }
+
+    /// Julian date of given instant, expressed in given time scale.
+    pub fn from_utc(time: DateTime<Utc>, time_scale: TimeScale) -> Self {
+        time_scale.from_utc(time).into()
+    }
+
+    /// Time shown by this date, or `None` when it is out of the range of
+    /// `NaiveDateTime`.
+    pub fn to_naive(&self) -> Option<NaiveDateTime> {
+        // Julian day `UNIX_EPOCH_JD` starts at noon of the day before the
+        // Unix epoch.
+        let days = self.day.checked_sub(UNIX_EPOCH_JD + 1)?;
+        let nanos = ((self.fraction + 0.5) * NANOS_PER_DAY).round() as i64;
+        let date = NaiveDate::from_num_days_from_ce_opt(
+            i32::try_from(days.checked_add(UNIX_EPOCH_DAYS_FROM_CE)?).ok()?,
+        )?;
+        date.and_time(NaiveTime::MIN)
+            .checked_add_signed(Duration::nanoseconds(nanos))
+    }
+
+    /// Instant at which clocks running in given time scale show this date,
+    /// or `None` when it is out of the range of `DateTime`.
+    pub fn to_utc(&self, time_scale: TimeScale) -> Option<DateTime<Utc>> {
+        let time = self.to_naive()?;
+        // Time scales are minutes apart at most, keep clear of the limits.
+        time.checked_sub_signed(Duration::hours(1))?
+            .checked_add_signed(Duration::hours(2))?;
+        Some(time_scale.to_utc(time))
+    }
+}
+
+impl From<f64> for JulianDate {
+    fn from(jd: f64) -> Self {
+        JulianDate::new(0, jd)
+    }
+}
+
+impl From<NaiveDateTime> for JulianDate {
+    fn from(time: NaiveDateTime) -> Self {
+        let time = time.and_utc();
+        let seconds = time.timestamp();
+        let days = seconds.div_euclid(86400);
+        let nanos = seconds.rem_euclid(86400) as f64 * 1e9 + time.timestamp_subsec_nanos() as f64;
+        // Julian day starts at noon.
+        JulianDate::new(UNIX_EPOCH_JD + days, 0.5 + nanos / NANOS_PER_DAY)
+    }
+}
+
+impl FromStr for JulianDate {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        match s.split_once('.') {
+            // Parse both parts of plain decimals separately to keep all the
+            // digits.
+            Some((day, fraction)) if is_digits(day) && is_digits(fraction) => Ok(JulianDate::new(
+                day.parse::<f64>()? as i64,
+                format!("0.{}", fraction).parse()?,
+            )),
+            _ => Ok(s.parse::<f64>()?.into()),
+        }
+    }
+}
+
+impl fmt::Display for JulianDate {
+    /// Format the date the way Horizons does, with 9 decimal places.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut day = self.day;
+        let mut fraction = (self.fraction * 1e9).round() as i64;
+        if fraction >= 1_000_000_000 {
+            day += 1;
+            fraction -= 1_000_000_000;
+        }
+        write!(f, "{}.{:09}", day, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn naive(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32, ms: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_milli_opt(h, min, s, ms)
+            .unwrap()
+    }
+
+    #[test]
+    fn leap_seconds() {
+        assert_eq!(10, tai_minus_utc(naive(1960, 1, 1, 0, 0, 0, 0)));
+        assert_eq!(36, tai_minus_utc(naive(2016, 12, 31, 23, 59, 59, 0)));
+        assert_eq!(37, tai_minus_utc(naive(2017, 1, 1, 0, 0, 0, 0)));
+        assert_eq!(37, tai_minus_utc(naive(2022, 6, 19, 18, 0, 0, 0)));
+    }
+
+    #[test]
+    fn converting_utc_and_tt() {
+        let utc = Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap();
+        let tt = naive(2022, 6, 19, 18, 1, 9, 184);
+        assert_eq!(tt, TimeScale::Tt.from_utc(utc));
+        assert_eq!(utc, TimeScale::Tt.to_utc(tt));
+
+        // Around the last leap second.
+        let utc = Utc.with_ymd_and_hms(2016, 12, 31, 23, 59, 59).unwrap();
+        let tt = naive(2017, 1, 1, 0, 1, 7, 184);
+        assert_eq!(tt, TimeScale::Tt.from_utc(utc));
+        assert_eq!(utc, TimeScale::Tt.to_utc(tt));
+
+        let utc = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+        let tt = naive(2017, 1, 1, 0, 1, 9, 184);
+        assert_eq!(tt, TimeScale::Tt.from_utc(utc));
+        assert_eq!(utc, TimeScale::Tt.to_utc(tt));
+
+        assert_eq!(utc, TimeScale::Utc.to_utc(utc.naive_utc()));
+        assert_eq!(utc.naive_utc(), TimeScale::Utc.from_utc(utc));
+    }
+
+    #[test]
+    fn converting_utc_and_tdb() {
+        let utc = Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap();
+        let tdb = TimeScale::Tdb.from_utc(utc);
+        let tt = TimeScale::Tt.from_utc(utc);
+
+        assert!((tdb - tt).num_microseconds().unwrap().abs() <= 2000);
+        assert_eq!(utc, TimeScale::Tdb.to_utc(tdb));
+    }
+
+    #[test]
+    fn time_scale_suffixes() {
+        assert_eq!(Some(TimeScale::Utc), TimeScale::from_suffix("UT"));
+        assert_eq!(Some(TimeScale::Tt), TimeScale::from_suffix("TT"));
+        assert_eq!(Some(TimeScale::Tdb), TimeScale::from_suffix("TDB"));
+        assert_eq!(None, TimeScale::from_suffix("CT"));
+    }
+
+    #[test]
+    fn parsing_julian_dates() {
+        let jd: JulianDate = "2459805.330509259".parse().unwrap();
+        assert_eq!(2459805, jd.day());
+        assert_eq!(0.330509259, jd.fraction());
+        assert_eq!("2459805.330509259", jd.to_string());
+
+        assert_eq!(JulianDate::new(2459805, 0.0), "2459805".parse().unwrap());
+        assert_eq!(JulianDate::new(-2, 0.75), "-1.25".parse().unwrap());
+        assert_eq!(
+            JulianDate::new(2459750, 0.5),
+            "2.4597505e6".parse().unwrap()
+        );
+        assert_eq!(
+            JulianDate::new(2459750, 0.5),
+            "2459750.5E0".parse().unwrap()
+        );
+        assert!("2459805.33x".parse::<JulianDate>().is_err());
+        assert!("2459805.-5".parse::<JulianDate>().is_err());
+        assert!("".parse::<JulianDate>().is_err());
+    }
+
+    #[test]
+    fn normalizing_julian_dates() {
+        assert_eq!(JulianDate::new(11, 0.5), JulianDate::new(10, 1.5));
+        assert_eq!(JulianDate::new(9, 0.5), JulianDate::new(10, -0.5));
+        assert_eq!(JulianDate::new(2459750, 0.25), JulianDate::from(2459750.25));
+        assert_eq!(2459750.25, JulianDate::new(2459750, 0.25).to_f64());
+        assert_eq!(
+            "2459751.000000000",
+            JulianDate::new(2459750, 0.9999999999).to_string()
+        );
+    }
+
+    #[test]
+    fn converting_julian_dates() {
+        let time = naive(2022, 6, 19, 18, 0, 0, 0);
+        let jd = JulianDate::new(2459750, 0.25);
+        assert_eq!(jd, JulianDate::from(time));
+        assert_eq!(Some(time), jd.to_naive());
+
+        // J2000.0
+        assert_eq!(JulianDate::new(2451545, 0.0), JulianDate::from(j2000()));
+
+        // Before the Unix epoch.
+        let time = naive(1957, 10, 4, 19, 28, 34, 0);
+        assert_eq!(Some(time), JulianDate::from(time).to_naive());
+
+        // Sub-second epochs survive the round trip.
+        let time = NaiveDate::from_ymd_opt(2022, 8, 13)
+            .unwrap()
+            .and_hms_micro_opt(19, 55, 56, 123_456)
+            .unwrap();
+        assert_eq!(Some(time), JulianDate::from(time).to_naive());
+
+        let utc = Utc.with_ymd_and_hms(2022, 6, 19, 18, 0, 0).unwrap();
+        assert_eq!(jd, JulianDate::from_utc(utc, TimeScale::Utc));
+        assert_eq!(
+            Some(utc),
+            JulianDate::from_utc(utc, TimeScale::Tdb).to_utc(TimeScale::Tdb)
+        );
+
+        // Far beyond what chrono can represent.
+        let jd = JulianDate::new(4_000_000_000_000, 0.0);
+        assert_eq!(None, jd.to_naive());
+        assert_eq!(None, jd.to_utc(TimeScale::Utc));
+        assert_eq!(None, JulianDate::new(i64::MIN, 0.0).to_utc(TimeScale::Tdb));
+        let last = NaiveDate::MAX.and_hms_opt(23, 30, 0).unwrap();
+        assert_eq!(Some(last), JulianDate::from(last).to_naive());
+        assert_eq!(None, JulianDate::from(last).to_utc(TimeScale::Tt));
+    }
+}
@@ -4,7 +4,11 @@ use thiserror::Error;
 /// is possible.
 pub fn take_or_empty(value: &str, n: usize) -> (&str, &str) {
     if value.len() > n {
-        (&value[..n], &value[n..])
+        // Don't cut multi-byte characters in half.
+        let n = (n..value.len())
+            .find(|&n| value.is_char_boundary(n))
+            .unwrap_or(value.len());
+        value.split_at(n)
     } else {
         (value, "")
     }
@@ -15,12 +19,7 @@ pub fn take_or_empty(value: &str, n: usize) -> (&str, &str) {
 pub struct TakeExpectingError;
 
 pub fn take_expecting<'a>(value: &'a str, expected: &str) -> Result<&'a str, TakeExpectingError> {
-    let (prefix, rest) = (&value[..expected.len()], &value[expected.len()..]);
-    if prefix == expected {
-        Ok(rest)
-    } else {
-        Err(TakeExpectingError {})
-    }
+    value.strip_prefix(expected).ok_or(TakeExpectingError {})
 }
 
 #[cfg(test)]
@@ -33,11 +32,13 @@ mod tests {
         assert_eq!(("", "a"), take_or_empty("a", 0));
         assert_eq!(("ab", "cd"), take_or_empty("abcd", 2));
         assert_eq!(("ab", ""), take_or_empty("ab", 4));
+        assert_eq!(("aé", "b"), take_or_empty("aéb", 2));
     }
 
     #[test]
     fn test_take_expecting() {
         assert_eq!(Ok("b"), take_expecting("ab", "a"));
         assert_eq!(Err(TakeExpectingError {}), take_expecting("ba", "a"));
+        assert_eq!(Err(TakeExpectingError {}), take_expecting("a", "ab"));
     }
 }
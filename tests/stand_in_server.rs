@@ -0,0 +1,472 @@
+/// Tests in this module talk to a local stand-in of the Horizons API, serving
+/// canned responses. They document what the client sends and how it reacts
+/// to what it receives, without requiring Internet access.
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use chrono::{TimeZone, Utc};
+use rhorizons::*;
+
+/// Minimal HTTP server answering consecutive requests with `responses`, given
+/// as `(status, body)`. Returns its URL and the list of received request
+/// paths.
+fn serve(responses: Vec<(u16, String)>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api/horizons.api", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+
+    thread::spawn(move || {
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let path = request_line.split(' ').nth(1).unwrap_or_default();
+            received.lock().unwrap().push(path.to_string());
+
+            // Skip the headers, there is no body in GET requests.
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            write!(
+                stream,
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    (url, requests)
+}
+
+/// Wrap Horizons text output in JSON, the way their API does.
+fn json_result(result: &str) -> String {
+    format!(
+        "{{\"signature\":{{\"source\":\"NASA/JPL Horizons API\",\"version\":\"1.2\"}},\"result\":{}}}",
+        escape(result)
+    )
+}
+
+fn escape(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+fn client(url: &str) -> HorizonsClient {
+    HorizonsClient::builder()
+        .base_url(url)
+        .timeout(Duration::from_secs(5))
+        .max_retries(2)
+        .backoff(Backoff::Constant(Duration::from_millis(10)))
+        .build()
+        .unwrap()
+}
+
+const MAJOR_BODIES: &str = " Multiple major-bodies match string \"MB*\"
+
+  ID#      Name                               Designation  IAU/aliases/other
+  -------  ---------------------------------- -----------  -------------------
+        0  Solar System Barycenter                         SSB
+       10  Sun                                             Sol
+      399  Earth                                           Geocenter
+";
+
+#[tokio::test]
+async fn querying_custom_base_url() {
+    let (url, requests) = serve(vec![(200, json_result(MAJOR_BODIES))]);
+
+    let bodies = client(&url).major_bodies().await.unwrap();
+
+    assert_eq!(3, bodies.len());
+    assert_eq!(BodyId::EARTH, bodies[2].id);
+    assert_eq!(Some("Geocenter"), bodies[2].aliases.as_deref());
+    assert_eq!(
+        vec!["/api/horizons.api?COMMAND=MB".to_string()],
+        *requests.lock().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn retrying_failed_queries() {
+    let (url, requests) = serve(vec![
+        (503, "Service Unavailable".to_string()),
+        (200, json_result(MAJOR_BODIES)),
+    ]);
+
+    let bodies = client(&url).major_bodies().await.unwrap();
+
+    assert_eq!(3, bodies.len());
+    assert_eq!(2, requests.lock().unwrap().len());
+}
+
+#[tokio::test]
+async fn giving_up_after_max_retries() {
+    let (url, requests) = serve(vec![
+        (503, "Service Unavailable".to_string()),
+        (200, "not JSON".to_string()),
+        (503, "Service Unavailable".to_string()),
+    ]);
+
+    let error = client(&url).major_bodies().await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        Error::RetriesExhausted { attempts: 3, last } if matches!(*last, Error::HttpStatus(status) if status == 503)
+    ));
+    assert_eq!(3, requests.lock().unwrap().len());
+}
+
+#[tokio::test]
+async fn reporting_malformed_ephemeris() {
+    let (url, _) = serve(vec![(
+        200,
+        json_result(
+            "$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 Y = 2.484687803242536E+03 Z =-5.861602653492581E+03
+ VX=-3.362664133558439E-01 VY=                  VZ=-5.030275220358716E-03
+$$EOE",
+        ),
+    )]);
+
+    let error = client(&url)
+        .ephemeris_vector(BodyId::EARTH, Utc::now(), Utc::now())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        Error::Parse {
+            line: 4,
+            reason: ParseError::InvalidNumber(_),
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn surfacing_api_errors() {
+    let (url, requests) = serve(vec![(
+        400,
+        "{\"signature\":{\"source\":\"NASA/JPL Horizons API\",\"version\":\"1.2\"},\"error\":\"Unknown parameter 'FOO'\"}".to_string(),
+    )]);
+
+    let error = client(&url).major_bodies().await.unwrap_err();
+
+    assert!(matches!(error, Error::Horizons(message) if message == "Unknown parameter 'FOO'"));
+    assert_eq!(1, requests.lock().unwrap().len());
+}
+
+#[tokio::test]
+async fn retrying_busy_server_errors() {
+    let (url, requests) = serve(vec![
+        (
+            503,
+            "{\"signature\":{\"source\":\"NASA/JPL Horizons API\",\"version\":\"1.2\"},\"error\":\"server too busy\"}".to_string(),
+        ),
+        (200, json_result(MAJOR_BODIES)),
+    ]);
+
+    let bodies = client(&url).major_bodies().await.unwrap();
+
+    assert_eq!(3, bodies.len());
+    assert_eq!(2, requests.lock().unwrap().len());
+}
+
+#[tokio::test]
+async fn not_retrying_permanent_errors() {
+    let (url, requests) = serve(vec![(
+        200,
+        json_result(
+            "*******************************************************************************
+ No ephemeris for target \"Earth\" after A.D. 9999-DEC-30 12:00:00.0000 TDB
+*******************************************************************************",
+        ),
+    )]);
+
+    let error = client(&url)
+        .ephemeris_vector(BodyId::EARTH, Utc::now(), Utc::now())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::NoEphemeris(_)));
+    assert!(!error.is_transient());
+    assert_eq!(1, requests.lock().unwrap().len());
+}
+
+const VECTORS: &str = "$$SOE
+2459805.330509259 = A.D. 2022-Aug-13 19:55:56.0000 TDB
+ X = 1.870010427985840E+02 Y = 2.484687803242536E+03 Z =-5.861602653492581E+03
+ VX=-3.362664133558439E-01 VY= 1.344100266143978E-02 VZ=-5.030275220358716E-03
+ LT= 2.124544811851363E-02 RG= 6.369225112760676E+03 RR= 1.673381117437352E-18
+$$EOE";
+
+#[tokio::test]
+async fn querying_with_custom_center() {
+    let (url, requests) = serve(vec![(200, json_result(VECTORS))]);
+
+    let query = EphemerisQuery::new(
+        BodyId::MOON,
+        Utc.with_ymd_and_hms(2022, 8, 13, 19, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2022, 8, 13, 20, 0, 0).unwrap(),
+    )
+    .center(BodyId::EARTH)
+    .time_scale(TimeScale::Utc);
+    let vectors = client(&url).vectors(&query).await.unwrap();
+
+    assert_eq!(1, vectors.len());
+    assert_eq!(
+        vec!["/api/horizons.api?COMMAND=301&CENTER=500%40399&EPHEM_TYPE=VECTORS&TIME_TYPE=UT&START_TIME=2022-Aug-13-19%3A00%3A00&STOP_TIME=2022-Aug-13-20%3A00%3A00".to_string()],
+        *requests.lock().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn splitting_long_epoch_lists() {
+    let second = VECTORS
+        .replace("2459805.330509259", "2459805.372175926")
+        .replace("19:55:56", "20:55:56");
+    let (url, requests) = serve(vec![
+        (200, json_result(VECTORS)),
+        (200, json_result(&second)),
+    ]);
+
+    let epochs: Vec<f64> = (0..150).map(|n| 2459805.0 + n as f64).collect();
+    let vectors = client(&url)
+        .vectors(&EphemerisQuery::at_epochs(BodyId::EARTH, &epochs))
+        .await
+        .unwrap();
+
+    assert_eq!(2, vectors.len());
+    assert!(vectors[0].time < vectors[1].time);
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(2, requests.len());
+    assert!(requests[0].contains("TLIST=%272459805.000000000%27+%272459806.000000000%27"));
+    assert!(requests[1].contains("TLIST=%272459905.000000000%27"));
+}
+
+#[tokio::test]
+async fn recording_reference_frame() {
+    let result = format!("Reference frame : ICRF\n{}", VECTORS);
+    let (url, requests) = serve(vec![(200, json_result(&result))]);
+
+    let query = EphemerisQuery::new(BodyId::EARTH, Utc::now(), Utc::now())
+        .reference_plane(ReferencePlane::Frame)
+        .reference_system(ReferenceSystem::Icrf);
+    let vectors = client(&url).vectors(&query).await.unwrap();
+
+    assert_eq!(1, vectors.len());
+    assert_eq!(
+        Some(ReferenceFrame {
+            plane: ReferencePlane::Frame,
+            system: ReferenceSystem::Icrf,
+        }),
+        vectors.metadata.reference_frame
+    );
+    assert!(requests.lock().unwrap()[0].contains("&REF_PLANE=FRAME&REF_SYSTEM=ICRF"));
+}
+
+#[tokio::test]
+async fn requesting_aberration_corrections() {
+    let result = format!("Output type     : APPARENT cartesian states\n{}", VECTORS);
+    let (url, requests) = serve(vec![(200, json_result(&result))]);
+
+    let query = EphemerisQuery::new(BodyId::EARTH, Utc::now(), Utc::now())
+        .vector_correction(VectorCorrection::Apparent);
+    let vectors = client(&url).vectors(&query).await.unwrap();
+
+    assert_eq!(
+        Some(VectorCorrection::Apparent),
+        vectors.metadata.vector_correction
+    );
+    assert!(requests.lock().unwrap()[0].contains("&VEC_CORR=LT%2BS"));
+}
+
+#[tokio::test]
+async fn querying_csv_tables() {
+    let result = "            JDTDB,            Calendar Date (TDB),                      X,                      Y,                      Z,                     VX,                     VY,                     VZ,                     LT,                     RG,                     RR,
+$$SOE
+2459805.330509259, A.D. 2022-Aug-13 19:55:56.0000,  1.870010427985840E+02,  2.484687803242536E+03, -5.861602653492581E+03, -3.362664133558439E-01,  1.344100266143978E-02, -5.030275220358716E-03,  2.124544811851363E-02,  6.369225112760676E+03,  1.673381117437352E-18,
+$$EOE";
+    let (url, requests) = serve(vec![
+        (200, json_result(result)),
+        (200, json_result(VECTORS)),
+    ]);
+
+    let csv_client = HorizonsClient::builder()
+        .base_url(&url)
+        .csv_format(true)
+        .build()
+        .unwrap();
+    let csv = csv_client
+        .ephemeris_vector(BodyId::EARTH, Utc::now(), Utc::now())
+        .await
+        .unwrap();
+    let fixed_width = client(&url)
+        .ephemeris_vector(BodyId::EARTH, Utc::now(), Utc::now())
+        .await
+        .unwrap();
+
+    assert_eq!(fixed_width.items, csv.items);
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].ends_with("&CSV_FORMAT=YES"));
+    assert!(!requests[1].contains("CSV_FORMAT"));
+}
+
+#[tokio::test]
+async fn querying_observer_tables() {
+    let result = " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC
+$$SOE
+ 2022-Aug-13 00:00     03 26 01.13 +18 09 50.6
+$$EOE";
+    let (url, requests) = serve(vec![(200, json_result(result))]);
+
+    let observer = client(&url)
+        .ephemeris_observer(
+            BodyId::MARS,
+            BodyId::EARTH,
+            Utc::now(),
+            Utc::now(),
+            &[ObserverQuantity::AstrometricRaDec],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(1, observer.len());
+    assert!(observer[0].astrometric_ra.is_some());
+    assert_eq!(None, observer[0].apparent_ra);
+    assert!(requests.lock().unwrap()[0].contains(
+        "COMMAND=499&CENTER=500%40399&EPHEM_TYPE=OBSERVER&TIME_TYPE=UT&QUANTITIES=%271%27"
+    ));
+}
+
+#[tokio::test]
+async fn checking_observer_site() {
+    let result = "Center geodetic : 0.0, 0.0, -6378.137             {E-lon(deg),Lat(deg),Alt(km)}
+ Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC
+$$SOE
+ 2022-Aug-13 00:00     03 26 01.13 +18 09 50.6
+$$EOE";
+    let (url, requests) = serve(vec![(200, json_result(result))]);
+
+    let site = ObserverSite::Geodetic {
+        body: BodyId::EARTH,
+        longitude: 243.1104,
+        latitude: 35.4267,
+        altitude: 1.0,
+    };
+    let query = EphemerisQuery::new(BodyId::MARS, Utc::now(), Utc::now()).center(site);
+    let err = client(&url).observer(&query).await.unwrap_err();
+
+    assert!(matches!(err, Error::SiteMismatch { .. }));
+    assert!(requests.lock().unwrap()[0].contains(
+        "&CENTER=coord%40399&COORD_TYPE=GEODETIC&SITE_COORD=%27243.1104%2C35.4267%2C1%27"
+    ));
+}
+
+#[tokio::test]
+async fn filtering_visible_entries() {
+    let result = " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC  Azi____(a-app)___Elev
+$$SOE
+ 2022-Aug-13 03:00 Am  03 27 07.75 +18 13 13.1   70.984216  11.802645
+ 2022-Aug-13 04:00 Cm  03 27 15.61 +18 13 36.6    78.126453 22.432541
+$$EOE";
+    let (url, requests) = serve(vec![(200, json_result(result))]);
+
+    let query = EphemerisQuery::new(BodyId::MARS, Utc::now(), Utc::now())
+        .quantities(&[
+            ObserverQuantity::AstrometricRaDec,
+            ObserverQuantity::AzimuthElevation,
+        ])
+        .visibility(10.0, SolarPresence::NauticalTwilight);
+    let observer = client(&url).observer(&query).await.unwrap();
+
+    assert_eq!(1, observer.len());
+    assert_eq!(
+        SolarPresence::AstronomicalTwilight,
+        observer[0].solar_presence
+    );
+    assert!(
+        requests.lock().unwrap()[0].contains("&QUANTITIES=%271%2C4%27&ELEV_CUT=10&SKIP_DAYLT=YES")
+    );
+}
+
+#[tokio::test]
+async fn querying_rise_transit_set() {
+    let result = " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC  Azi____(a-app)___Elev
+$$SOE
+ 2022-Aug-13 22:41  mr 03 28 41.41 +18 17 46.0   68.374922  -0.566271
+ 2022-Aug-14 06:12 *mt 03 29 16.08 +18 19 30.7  180.000000  58.917394
+ 2022-Aug-14 13:43 *ms 03 29 50.41 +18 21 13.1  291.635283  -0.566285
+$$EOE";
+    let (url, requests) = serve(vec![(200, json_result(result))]);
+
+    let query = EphemerisQuery::new(BodyId::MARS, Utc::now(), Utc::now())
+        .center(ObserverSite::Observatory {
+            code: "675".to_string(),
+            body: BodyId::EARTH,
+        })
+        .quantities(&[ObserverQuantity::AstrometricRaDec]);
+    let events = client(&url)
+        .rise_transit_set(&query, RiseSetHorizon::TrueVisual)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        vec![TargetEvent::Rise, TargetEvent::Transit, TargetEvent::Set],
+        events.iter().map(|e| e.event).collect::<Vec<_>>()
+    );
+    assert_eq!(180.0, events[1].azimuth);
+    assert_eq!("2459805.758333333", events[1].julian_date.to_string());
+    assert_eq!(58.917394, events[1].elevation);
+    assert!(requests.lock().unwrap()[0].contains("&R_T_S_ONLY=TVH&QUANTITIES=%271%2C4%27"));
+}
+
+#[tokio::test]
+async fn rise_transit_set_ignores_visibility() {
+    let result = " Date__(UT)__HR:MN     R.A._____(ICRF)_____DEC  Azi____(a-app)___Elev
+$$SOE
+ 2022-Aug-13 22:41  mr 03 28 41.41 +18 17 46.0   68.374922  -0.566271
+ 2022-Aug-14 06:12 *mt 03 29 16.08 +18 19 30.7  180.000000  58.917394
+ 2022-Aug-14 13:43 *ms 03 29 50.41 +18 21 13.1  291.635283  -0.566285
+$$EOE";
+    let (url, requests) = serve(vec![(200, json_result(result))]);
+
+    let query = EphemerisQuery::new(BodyId::MARS, Utc::now(), Utc::now())
+        .center(ObserverSite::Observatory {
+            code: "675".to_string(),
+            body: BodyId::EARTH,
+        })
+        .visibility(15.0, SolarPresence::NauticalTwilight);
+    let events = client(&url)
+        .rise_transit_set(&query, RiseSetHorizon::TrueVisual)
+        .await
+        .unwrap();
+
+    // Rise and set are below the minimum elevation.
+    assert_eq!(
+        vec![TargetEvent::Rise, TargetEvent::Transit, TargetEvent::Set],
+        events.iter().map(|e| e.event).collect::<Vec<_>>()
+    );
+    let request = &requests.lock().unwrap()[0];
+    assert!(request.contains("&R_T_S_ONLY=TVH"));
+    assert!(!request.contains("ELEV_CUT"));
+    assert!(!request.contains("SKIP_DAYLT"));
+}